@@ -67,6 +67,9 @@ mod dario_fsm_contract {
 
     const JOURNAL_LEN: usize = ACCOUNT_BYTES + 8 + 8 + 4;
 
+    /// Blocks per UTC day at Dusk's 10 s target block time.
+    const BLOCKS_PER_DAY: u64 = 8_640;
+
     #[derive(Clone, Copy)]
     struct PlayerState {
         current_state: DarioState,
@@ -112,6 +115,11 @@ mod dario_fsm_contract {
         players: BTreeMap<AccountKey, PlayerState>,
         proven: BTreeMap<Account, ProvenStats>,
         used_seeds: BTreeSet<(Account, u64)>,
+        /// Proven (score, ticks) per day and account, for runs on that
+        /// day's [`dash_zk::daily`] seed.
+        daily: BTreeMap<u32, BTreeMap<Account, (u64, u32)>>,
+        /// (block height, day) anchor of the daily clock, set by the owner.
+        daily_clock: Option<(u64, u32)>,
     }
 
     impl DarioFSM {
@@ -123,6 +131,8 @@ mod dario_fsm_contract {
                 players: BTreeMap::new(),
                 proven: BTreeMap::new(),
                 used_seeds: BTreeSet::new(),
+                daily: BTreeMap::new(),
+                daily_clock: None,
             }
         }
 
@@ -259,19 +269,23 @@ mod dario_fsm_contract {
         /// and binds the caller's Moonlight account into the public inputs,
         /// so the proof cannot be replayed by anyone else.
         ///
+        /// A daily challenge seed is only accepted on its own day by the
+        /// [`Self::set_daily_clock`] clock, and may be played any number of
+        /// times that day: each run must beat the account's entry in
+        /// [`Self::daily_leaderboard`] (a higher score, or the same score in
+        /// fewer ticks), which it then replaces. Any other seed is accepted
+        /// once per account.
+        ///
         /// Panics if the caller is not a Moonlight transaction, the seed was
-        /// already used by this account, `score`/`ticks` are out of range, or
-        /// the proof does not verify.
+        /// already used by this account, the seed is a daily seed for
+        /// another day than today or does not beat the account's daily
+        /// entry, `score`/`ticks` are out of range, or the proof does not
+        /// verify.
         pub fn submit_zk_run(&mut self, seed: u64, score: u64, ticks: u32, proof: Vec<u8>) {
             let pk = moonlight_public_key()
                 .expect("submit_zk_run must be called directly via a Moonlight transaction");
             let account: Account = pk.to_bytes();
 
-            assert!(
-                self.used_seeds.insert((account, seed)),
-                "seed already used by this account"
-            );
-
             // The circuit assumes the contract enforces public-input ranges.
             assert!(
                 (1..=dash_zk::MAX_TICKS).contains(&ticks),
@@ -279,6 +293,27 @@ mod dario_fsm_contract {
             );
             assert!(score <= dash_zk::MAX_RANKED_SCORE, "score out of range");
 
+            let daily = dash_zk::daily::daily_day(seed, dash_zk::daily::DAILY_DOMAIN);
+            match daily {
+                Some(day) => {
+                    let today = self.today();
+                    assert!(
+                        today.is_some_and(|today| day <= today),
+                        "daily challenge not open yet"
+                    );
+                    assert!(today == Some(day), "daily challenge closed");
+                    let best = self.daily.get(&day).and_then(|runs| runs.get(&account));
+                    assert!(
+                        best.is_none_or(|&best| dash_zk::daily::beats(score, ticks, best)),
+                        "run does not beat this account's daily best"
+                    );
+                }
+                None => assert!(
+                    self.used_seeds.insert((account, seed)),
+                    "seed already used by this account"
+                ),
+            }
+
             let inputs = zk_public_inputs(seed, score, ticks, &account);
             let prepared = prepare_inputs(ZK_GAMMA_ABC, &inputs);
 
@@ -296,6 +331,13 @@ mod dario_fsm_contract {
             stats.runs = stats.runs.saturating_add(1);
             stats.total_ticks = stats.total_ticks.saturating_add(ticks as u64);
 
+            if let Some(day) = daily {
+                self.daily
+                    .entry(day)
+                    .or_default()
+                    .insert(account, (score, ticks));
+            }
+
             dusk_core::abi::emit("zk_run", score);
         }
 
@@ -327,6 +369,54 @@ mod dario_fsm_contract {
             entries.truncate(10);
             entries
         }
+
+        /// Returns the seed of daily challenge `day` (UTC days since the
+        /// Unix epoch).
+        pub fn daily_seed(&self, day: u32) -> u64 {
+            dash_zk::daily::daily_seed(day, dash_zk::daily::DAILY_DOMAIN)
+        }
+
+        /// Anchors the daily clock: the current block is in UTC day `day`,
+        /// and the day advances every [`BLOCKS_PER_DAY`] blocks from here.
+        /// Daily runs are only accepted on the clock's day; call again to
+        /// correct drift in the block time.
+        ///
+        /// Panics unless called directly via a Moonlight transaction from
+        /// the contract owner.
+        pub fn set_daily_clock(&mut self, day: u32) {
+            let pk = moonlight_public_key()
+                .expect("set_daily_clock must be called directly via a Moonlight transaction");
+            assert!(
+                pk.to_bytes() == abi::self_owner_raw(),
+                "only the owner can set the daily clock"
+            );
+            self.daily_clock = Some((abi::block_height(), day));
+        }
+
+        /// The current daily challenge day by the block-height clock, or
+        /// `None` before the owner has set it.
+        fn today(&self) -> Option<u32> {
+            let (height, day) = self.daily_clock?;
+            let days = abi::block_height().saturating_sub(height) / BLOCKS_PER_DAY;
+            Some(day.saturating_add(days as u32))
+        }
+
+        /// Returns the top 10 proven runs on daily challenge `day` as
+        /// (account, score, ticks), each account's best, sorted by score
+        /// descending, then by fewer ticks.
+        pub fn daily_leaderboard(&self, day: u32) -> Vec<(String, u64, u32)> {
+            let Some(day) = self.daily.get(&day) else {
+                return Vec::new();
+            };
+            let mut entries: Vec<_> = day
+                .iter()
+                .map(|(account, &(score, ticks))| {
+                    (bs58::encode(account).into_string(), score, ticks)
+                })
+                .collect();
+            dash_zk::daily::rank(&mut entries);
+            entries
+        }
     }
 
     impl Default for DarioFSM {
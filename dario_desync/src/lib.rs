@@ -0,0 +1,176 @@
+//! # Dario desync — checkpointed state hashes
+//!
+//! Desync detection shared by `dash_core` and `dash_zk`. A sim that opts
+//! in with [`HashedSim::hash_states`] folds its full state into a rolling
+//! [`HashedSim::state_hash`] after every tick it advances, so two
+//! executions of the same trace (browser WASM, native, zkVM guest) agree
+//! on the hash at tick `t` exactly when they agreed on every tick up to
+//! `t`. Record hashes every `N` ticks on both sides and
+//! [`first_divergence`] names the first checkpoint that differs; re-record
+//! that window with `N = 1` to pin the exact tick. Ticks after the last
+//! checkpoint are only covered by comparing the final `state_hash`.
+//!
+//! Hashing is opt-in because it costs dozens of [`mix`]es per tick, which
+//! replays and the zkVM guest, checking only the outcome, do not need.
+#![no_std]
+
+const K: u64 = 0x517c_c1b7_2722_0a95;
+
+/// Folds one word into a running hash (FxHash step).
+pub fn mix(h: u64, word: u64) -> u64 {
+    (h.rotate_left(5) ^ word).wrapping_mul(K)
+}
+
+/// A deterministic sim with a rolling state hash.
+pub trait HashedSim {
+    /// Advances one tick on `input`.
+    fn tick(&mut self, input: u8);
+    fn ticks(&self) -> u32;
+    fn over(&self) -> bool;
+    /// Rolling hash over every post-tick state since hashing was turned
+    /// on; 0 before that.
+    fn state_hash(&self) -> u64;
+    /// Turns hashing on from the next tick. Sims compared against each
+    /// other must turn it on at the same tick.
+    fn hash_states(&mut self);
+}
+
+/// Records [`HashedSim::state_hash`] every `every` ticks into a
+/// caller-provided buffer. Checkpoint `i` is the hash after tick
+/// `(i + 1) * every`.
+pub struct Checkpoints<'a> {
+    every: u32,
+    hashes: &'a mut [u64],
+    len: usize,
+}
+
+impl<'a> Checkpoints<'a> {
+    /// Panics if `every` is zero.
+    pub fn new(every: u32, buf: &'a mut [u64]) -> Self {
+        assert!(every > 0, "checkpoint interval must be positive");
+        Self {
+            every,
+            hashes: buf,
+            len: 0,
+        }
+    }
+
+    /// Call after every tick. Stores the hash when the sim sits on the next
+    /// checkpoint tick; extra calls and a full buffer are ignored.
+    pub fn record(&mut self, sim: &impl HashedSim) {
+        let next = (self.len as u64 + 1) * u64::from(self.every);
+        if u64::from(sim.ticks()) == next && self.len < self.hashes.len() {
+            self.hashes[self.len] = sim.state_hash();
+            self.len += 1;
+        }
+    }
+
+    pub fn every(&self) -> u32 {
+        self.every
+    }
+
+    /// The hashes recorded so far.
+    pub fn hashes(&self) -> &[u64] {
+        &self.hashes[..self.len]
+    }
+}
+
+/// Turns hashing on, then replays `trace` on `sim` (stopping at game
+/// over), recording checkpoints into `out`. Returns the number of
+/// checkpoints written.
+pub fn record_checkpoints(
+    mut sim: impl HashedSim,
+    trace: &[u8],
+    every: u32,
+    out: &mut [u64],
+) -> usize {
+    sim.hash_states();
+    let mut cps = Checkpoints::new(every, out);
+    for &input in trace {
+        if sim.over() {
+            break;
+        }
+        sim.tick(input);
+        cps.record(&sim);
+    }
+    cps.len
+}
+
+/// The tick of the first checkpoint at which `a` and `b` (both recorded
+/// every `every` ticks) differ, or `None` if they agree. When one list is a
+/// strict prefix of the other, the first unmatched checkpoint is reported.
+/// The divergence itself happened in `(tick - every, tick]`.
+pub fn first_divergence(every: u32, a: &[u64], b: &[u64]) -> Option<u32> {
+    let i = a
+        .iter()
+        .zip(b)
+        .position(|(x, y)| x != y)
+        .or((a.len() != b.len()).then_some(a.len().min(b.len())))?;
+    Some((i as u32 + 1) * every)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sums its inputs; over once the sum reaches 10.
+    struct Counter {
+        ticks: u32,
+        sum: u32,
+        hashing: bool,
+        hash: u64,
+    }
+
+    impl HashedSim for Counter {
+        fn tick(&mut self, input: u8) {
+            self.ticks += 1;
+            self.sum += u32::from(input);
+            if self.hashing {
+                self.hash = mix(self.hash, u64::from(self.sum));
+            }
+        }
+        fn ticks(&self) -> u32 {
+            self.ticks
+        }
+        fn over(&self) -> bool {
+            self.sum >= 10
+        }
+        fn state_hash(&self) -> u64 {
+            self.hash
+        }
+        fn hash_states(&mut self) {
+            self.hashing = true;
+        }
+    }
+
+    fn counter() -> Counter {
+        Counter {
+            ticks: 0,
+            sum: 0,
+            hashing: false,
+            hash: 0,
+        }
+    }
+
+    #[test]
+    fn checkpoints_land_on_every_nth_tick_until_game_over() {
+        let mut out = [0u64; 8];
+        let n = record_checkpoints(counter(), &[1; 20], 3, &mut out);
+        // Over after tick 10: checkpoints at 3, 6 and 9.
+        assert_eq!(n, 3);
+        let mut sim = counter();
+        sim.hash_states();
+        for _ in 0..3 {
+            sim.tick(1);
+        }
+        assert_eq!(out[0], sim.state_hash());
+        assert_ne!(out[0], 0);
+    }
+
+    #[test]
+    fn first_divergence_reports_mismatches_and_prefixes() {
+        assert_eq!(first_divergence(4, &[1, 2, 3], &[1, 2, 3]), None);
+        assert_eq!(first_divergence(4, &[1, 2, 3], &[1, 5, 3]), Some(8));
+        assert_eq!(first_divergence(4, &[1, 2], &[1, 2, 3]), Some(12));
+    }
+}
@@ -0,0 +1,129 @@
+//! # Dario diagnose — shared shapes for diagnostic replays
+//!
+//! `dash_core::diagnose` and `dash_zk::diagnose` replay a trace and explain
+//! how the run ended. The sims differ in what a death looks like (slots
+//! and fp hitboxes versus schedule indices and fp100 edges), so each keeps
+//! its own `Death` and `Hitbox`; everything else they report has the one
+//! shape defined here, so tools can treat both diagnoses alike.
+#![no_std]
+
+use dario_fsm::DarioState;
+
+/// Form changes kept by a diagnosis; later ones are dropped.
+pub const MAX_FORM_CHANGES: usize = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FormChange {
+    pub tick: u32,
+    pub from: DarioState,
+    pub to: DarioState,
+}
+
+/// How a run ended; `D` is the sim's description of a death.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ending<D> {
+    Died(D),
+    /// The sim's score cap was reached (ranked `dash_zk` runs only).
+    ScoreCap,
+    /// The sim's `MAX_TICKS` was reached.
+    TimeUp,
+    /// The trace ran out with Dario still running.
+    Unfinished,
+}
+
+/// The form changes of a run, the first [`MAX_FORM_CHANGES`] of them.
+#[derive(Clone, Debug)]
+pub struct FormLog {
+    changes: [FormChange; MAX_FORM_CHANGES],
+    len: usize,
+}
+
+impl FormLog {
+    pub const fn new() -> Self {
+        FormLog {
+            changes: [FormChange {
+                tick: 0,
+                from: DarioState::Regular,
+                to: DarioState::Regular,
+            }; MAX_FORM_CHANGES],
+            len: 0,
+        }
+    }
+
+    /// Records a change; ignored once the log is full.
+    pub fn push(&mut self, change: FormChange) {
+        if self.len < MAX_FORM_CHANGES {
+            self.changes[self.len] = change;
+            self.len += 1;
+        }
+    }
+
+    pub fn as_slice(&self) -> &[FormChange] {
+        &self.changes[..self.len]
+    }
+}
+
+impl Default for FormLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The first tick each of a sim's `N` capacities ran out, by index into
+/// its `Capacity::ALL`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FirstTicks<const N: usize>([Option<u32>; N]);
+
+impl<const N: usize> FirstTicks<N> {
+    pub const fn new() -> Self {
+        FirstTicks([None; N])
+    }
+
+    /// Notes that capacity `index` is out at `tick`; only the first tick
+    /// sticks.
+    pub fn note(&mut self, index: usize, tick: u32) {
+        self.0[index].get_or_insert(tick);
+    }
+
+    /// Each capacity that ran out, with the first tick it did, in `all`
+    /// order.
+    pub fn iter<C: Copy>(&self, all: [C; N]) -> impl Iterator<Item = (C, u32)> {
+        all.into_iter()
+            .zip(self.0)
+            .filter_map(|(c, tick)| Some((c, tick?)))
+    }
+}
+
+impl<const N: usize> Default for FirstTicks<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_keep_the_first_entries() {
+        let mut forms = FormLog::new();
+        for tick in 0..MAX_FORM_CHANGES as u32 + 5 {
+            forms.push(FormChange {
+                tick,
+                from: DarioState::Regular,
+                to: DarioState::Super,
+            });
+        }
+        assert_eq!(forms.as_slice().len(), MAX_FORM_CHANGES);
+        assert_eq!(
+            forms.as_slice().last().unwrap().tick,
+            MAX_FORM_CHANGES as u32 - 1
+        );
+
+        let mut first = FirstTicks::<3>::new();
+        first.note(2, 40);
+        first.note(0, 50);
+        first.note(2, 60);
+        assert!(first.iter(['a', 'b', 'c']).eq([('a', 50), ('c', 40)]));
+    }
+}
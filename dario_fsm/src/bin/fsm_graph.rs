@@ -0,0 +1,25 @@
+//! Prints the Dario state machine as a graph for docs and design work.
+//!
+//! Usage: fsm_graph <dot|mermaid|json> [--hide-self-loops]
+//!
+//! The output is generated from `transition` itself, so it never drifts
+//! from the FSM the sims and the contract run.
+
+use dario_fsm::graph::{write_dot, write_json, write_mermaid};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let hide = args.iter().skip(2).any(|a| a == "--hide-self-loops");
+    let mut out = String::new();
+    let written = match args.get(1).map(String::as_str) {
+        Some("dot") => write_dot(&mut out, hide),
+        Some("mermaid") => write_mermaid(&mut out, hide),
+        Some("json") => write_json(&mut out, hide),
+        _ => {
+            eprintln!("usage: fsm_graph <dot|mermaid|json> [--hide-self-loops]");
+            std::process::exit(1);
+        }
+    };
+    written.expect("formatting into a String cannot fail");
+    print!("{out}");
+}
@@ -0,0 +1,109 @@
+//! Graph export of the Dario state machine.
+//!
+//! Walks every `(DarioState, Event)` pair through [`transition`] and writes
+//! the resulting edges as Graphviz DOT, a Mermaid `stateDiagram-v2` or a JSON
+//! adjacency list. Writers target [`core::fmt::Write`], so they work in
+//! `no_std` and with a plain `String`.
+//!
+//! # Examples
+//!
+//! ```
+//! use dario_fsm::graph::write_dot;
+//!
+//! let mut dot = String::new();
+//! write_dot(&mut dot, true).unwrap();
+//! assert!(dot.contains("Regular -> Super [label=\"Espresso\"];"));
+//! assert!(!dot.contains("GameOver -> GameOver"));
+//! ```
+
+use core::fmt::{self, Write};
+
+use crate::{transition, DarioState, Event};
+
+/// A single transition: `from` moves to `to` on `event`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Edge {
+    pub from: DarioState,
+    pub event: Event,
+    pub to: DarioState,
+}
+
+impl Edge {
+    /// True when the event leaves the state unchanged.
+    pub fn is_self_loop(&self) -> bool {
+        self.from == self.to
+    }
+}
+
+/// Every edge of the machine, grouped by source state in discriminant
+/// order. With `hide_self_loops`, no-op transitions are skipped.
+///
+/// ```
+/// use dario_fsm::graph::edges;
+///
+/// assert_eq!(edges(false).count(), 25);
+/// assert!(edges(true).all(|e| !e.is_self_loop()));
+/// ```
+pub fn edges(hide_self_loops: bool) -> impl Iterator<Item = Edge> {
+    DarioState::ALL
+        .into_iter()
+        .flat_map(|from| {
+            Event::ALL.into_iter().map(move |event| Edge {
+                from,
+                event,
+                to: transition(from, event),
+            })
+        })
+        .filter(move |edge| !(hide_self_loops && edge.is_self_loop()))
+}
+
+/// Writes the machine as a Graphviz DOT digraph.
+pub fn write_dot<W: Write>(out: &mut W, hide_self_loops: bool) -> fmt::Result {
+    writeln!(out, "digraph dario {{")?;
+    writeln!(out, "    rankdir=LR;")?;
+    for state in DarioState::ALL {
+        writeln!(out, "    {state:?};")?;
+    }
+    for Edge { from, event, to } in edges(hide_self_loops) {
+        writeln!(out, "    {from:?} -> {to:?} [label=\"{event:?}\"];")?;
+    }
+    writeln!(out, "}}")
+}
+
+/// Writes the machine as a Mermaid `stateDiagram-v2`.
+pub fn write_mermaid<W: Write>(out: &mut W, hide_self_loops: bool) -> fmt::Result {
+    writeln!(out, "stateDiagram-v2")?;
+    writeln!(out, "    [*] --> {:?}", DarioState::Regular)?;
+    for Edge { from, event, to } in edges(hide_self_loops) {
+        writeln!(out, "    {from:?} --> {to:?}: {event:?}")?;
+    }
+    Ok(())
+}
+
+/// Writes the machine as a JSON adjacency list keyed by source state:
+/// `{"Regular": [{"event": "Espresso", "to": "Super"}, ...], ...}`.
+///
+/// Every state gets a key, even when all its edges are hidden.
+pub fn write_json<W: Write>(out: &mut W, hide_self_loops: bool) -> fmt::Result {
+    out.write_str("{")?;
+    for (i, from) in DarioState::ALL.into_iter().enumerate() {
+        if i > 0 {
+            out.write_str(",")?;
+        }
+        write!(out, "\n  \"{from:?}\": [")?;
+        let mut first = true;
+        for edge in edges(hide_self_loops).filter(|e| e.from == from) {
+            if !first {
+                out.write_str(",")?;
+            }
+            first = false;
+            write!(
+                out,
+                "\n    {{\"event\": \"{:?}\", \"to\": \"{:?}\"}}",
+                edge.event, edge.to
+            )?;
+        }
+        out.write_str(if first { "]" } else { "\n  ]" })?;
+    }
+    out.write_str("\n}\n")
+}
@@ -8,6 +8,8 @@
 //! in the `transition` function, which computes the new state of Dario based on the current state and an event.
 #![no_std]
 
+pub mod graph;
+
 /// Represents the various states that Dario can be in.
 ///
 /// # Examples
@@ -28,6 +30,17 @@ pub enum DarioState {
     GameOver,
 }
 
+impl DarioState {
+    /// Every state, in discriminant order.
+    pub const ALL: [DarioState; 5] = [
+        DarioState::Regular,
+        DarioState::Super,
+        DarioState::Fire,
+        DarioState::Cape,
+        DarioState::GameOver,
+    ];
+}
+
 /// Represents the different events that can cause state transitions for Dario.
 ///
 /// # Examples
@@ -48,6 +61,17 @@ pub enum Event {
     Revive,
 }
 
+impl Event {
+    /// Every event, in discriminant order.
+    pub const ALL: [Event; 5] = [
+        Event::Espresso,
+        Event::ChiliPepper,
+        Event::TableClothCape,
+        Event::TakeDamage,
+        Event::Revive,
+    ];
+}
+
 impl TryFrom<u32> for Event {
     type Error = ();
 
@@ -63,6 +87,58 @@ impl TryFrom<u32> for Event {
     }
 }
 
+/// A power-up item, as picked up in the sims or held in reserve.
+///
+/// # Examples
+///
+/// ```
+/// use dario_fsm::{DarioState, Event, PowerUp};
+///
+/// assert_eq!(PowerUp::ChiliPepper.event(), Event::ChiliPepper);
+/// assert_eq!(PowerUp::from_form(DarioState::Cape), Some(PowerUp::TableClothCape));
+/// ```
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerUp {
+    Espresso,
+    ChiliPepper,
+    TableClothCape,
+}
+
+impl PowerUp {
+    /// The FSM event this item triggers when picked up or deployed.
+    pub fn event(self) -> Event {
+        match self {
+            PowerUp::Espresso => Event::Espresso,
+            PowerUp::ChiliPepper => Event::ChiliPepper,
+            PowerUp::TableClothCape => Event::TableClothCape,
+        }
+    }
+
+    /// The item that grants `state`, or `None` for unpowered states.
+    pub fn from_form(state: DarioState) -> Option<Self> {
+        match state {
+            DarioState::Super => Some(PowerUp::Espresso),
+            DarioState::Fire => Some(PowerUp::ChiliPepper),
+            DarioState::Cape => Some(PowerUp::TableClothCape),
+            DarioState::Regular | DarioState::GameOver => None,
+        }
+    }
+}
+
+impl TryFrom<u32> for PowerUp {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            value if value == Self::Espresso as u32 => Ok(Self::Espresso),
+            value if value == Self::ChiliPepper as u32 => Ok(Self::ChiliPepper),
+            value if value == Self::TableClothCape as u32 => Ok(Self::TableClothCape),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Transitions Dario's state based on the provided event.
 ///
 /// # Arguments
@@ -101,3 +177,222 @@ pub fn transition(state: DarioState, event: Event) -> DarioState {
         (current_state, _) => current_state,
     }
 }
+
+/// Returns true for the power-up forms (`Super`, `Fire` and `Cape`).
+pub fn is_powered(state: DarioState) -> bool {
+    matches!(
+        state,
+        DarioState::Super | DarioState::Fire | DarioState::Cape
+    )
+}
+
+/// Tick-aware transition with optional power-up expiry.
+///
+/// An event, when present, is applied through [`transition`] and takes
+/// precedence. Without one, a powered form that has been held for at least
+/// `expire_after` ticks reverts to `Regular`. `ticks_in_state` is tracked
+/// by the caller and restarts whenever the form changes. With
+/// `expire_after == None` this is exactly [`transition`], so forms last
+/// until damage as before.
+///
+/// # Arguments
+///
+/// * `state` - The current state of Dario.
+/// * `event` - The event happening this tick, if any.
+/// * `ticks_in_state` - Ticks spent in `state` so far.
+/// * `expire_after` - Power-up lifetime in ticks, or `None` to never expire.
+///
+/// # Examples
+///
+/// ```
+/// use dario_fsm::{step, DarioState, Event};
+///
+/// let state = step(DarioState::Regular, Some(Event::Espresso), 0, Some(300));
+/// assert_eq!(state, DarioState::Super);
+///
+/// assert_eq!(step(state, None, 299, Some(300)), DarioState::Super);
+/// assert_eq!(step(state, None, 300, Some(300)), DarioState::Regular);
+/// assert_eq!(step(state, None, 300, None), DarioState::Super);
+/// ```
+pub fn step(
+    state: DarioState,
+    event: Option<Event>,
+    ticks_in_state: u32,
+    expire_after: Option<u32>,
+) -> DarioState {
+    match (event, expire_after) {
+        (Some(event), _) => transition(state, event),
+        (None, Some(limit)) if is_powered(state) && ticks_in_state >= limit => DarioState::Regular,
+        (None, _) => state,
+    }
+}
+
+/// Dario's form plus a one-slot reserve item.
+///
+/// A pickup that would not change the form is stored, and a pickup (or
+/// deploy) that replaces a powered form stores the item of the form it
+/// displaced. The slot holds one item and the newest one wins: storing
+/// into a full reserve drops the item already there, whichever is better.
+/// Damage only affects the form.
+///
+/// # Examples
+///
+/// ```
+/// use dario_fsm::{DarioState, Event, Loadout, PowerUp};
+///
+/// let loadout = Loadout::new().pick_up(PowerUp::ChiliPepper);
+/// assert_eq!(loadout.form, DarioState::Fire);
+///
+/// // Espresso cannot improve Fire, so it goes into the reserve.
+/// let loadout = loadout.pick_up(PowerUp::Espresso);
+/// assert_eq!(loadout.reserve, Some(PowerUp::Espresso));
+///
+/// let loadout = loadout.apply(Event::TakeDamage).deploy();
+/// assert_eq!(loadout.form, DarioState::Super);
+/// assert_eq!(loadout.reserve, None);
+/// ```
+///
+/// A pickup with the reserve full overwrites it:
+///
+/// ```
+/// use dario_fsm::{DarioState, Loadout, PowerUp};
+///
+/// let full = Loadout {
+///     form: DarioState::Fire,
+///     reserve: Some(PowerUp::TableClothCape),
+/// };
+/// // Stored: the Espresso replaces the cape.
+/// assert_eq!(full.pick_up(PowerUp::Espresso).reserve, Some(PowerUp::Espresso));
+///
+/// // Form change: Fire's Chili Pepper replaces the cape.
+/// let caped = full.pick_up(PowerUp::TableClothCape);
+/// assert_eq!(caped.form, DarioState::Cape);
+/// assert_eq!(caped.reserve, Some(PowerUp::ChiliPepper));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Loadout {
+    pub form: DarioState,
+    pub reserve: Option<PowerUp>,
+}
+
+impl Loadout {
+    /// `Regular` with an empty reserve.
+    pub const fn new() -> Self {
+        Self {
+            form: DarioState::Regular,
+            reserve: None,
+        }
+    }
+
+    /// Applies an event to the form only; the reserve is untouched.
+    pub fn apply(self, event: Event) -> Self {
+        Self {
+            form: transition(self.form, event),
+            ..self
+        }
+    }
+
+    /// Picks up `item`, storing it or the displaced form in the reserve and
+    /// dropping whatever the reserve held. Picking up as `Regular` keeps
+    /// the reserve, since no form is displaced.
+    pub fn pick_up(self, item: PowerUp) -> Self {
+        if self.form == DarioState::GameOver {
+            return self;
+        }
+        let form = transition(self.form, item.event());
+        if form == self.form {
+            return Self {
+                reserve: Some(item),
+                ..self
+            };
+        }
+        Self {
+            form,
+            reserve: PowerUp::from_form(self.form).or(self.reserve),
+        }
+    }
+
+    /// Deploys the reserve item. The slot then holds the displaced form's
+    /// item, if any. Does nothing when the item would not change the form.
+    pub fn deploy(self) -> Self {
+        let item = match self.reserve {
+            Some(item) if self.form != DarioState::GameOver => item,
+            _ => return self,
+        };
+        let form = transition(self.form, item.event());
+        if form == self.form {
+            return self;
+        }
+        Self {
+            form,
+            reserve: PowerUp::from_form(self.form),
+        }
+    }
+}
+
+impl Default for Loadout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hearts-mode health, an optional layer over the form-based damage rules.
+///
+/// Powered forms still absorb a hit by dropping to `Regular`. A hit taken as
+/// `Regular` costs a heart instead of ending the run, and `GameOver` only
+/// happens when the last heart is lost. `Revive` refills every heart.
+///
+/// # Examples
+///
+/// ```
+/// use dario_fsm::{DarioState, Event, Hearts};
+///
+/// let hearts = Hearts::full(2);
+/// let (state, hearts) = hearts.apply(DarioState::Fire, Event::TakeDamage);
+/// assert_eq!((state, hearts.left), (DarioState::Regular, 2));
+///
+/// let (state, hearts) = hearts.apply(state, Event::TakeDamage);
+/// assert_eq!((state, hearts.left), (DarioState::Regular, 1));
+///
+/// let (state, hearts) = hearts.apply(state, Event::TakeDamage);
+/// assert_eq!((state, hearts.left), (DarioState::GameOver, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hearts {
+    pub left: u8,
+    pub max: u8,
+}
+
+impl Hearts {
+    /// Full health with `max` hearts (at least one).
+    pub const fn full(max: u8) -> Self {
+        let max = if max == 0 { 1 } else { max };
+        Self { left: max, max }
+    }
+
+    /// Applies `event` to `state` under hearts rules.
+    pub fn apply(self, state: DarioState, event: Event) -> (DarioState, Hearts) {
+        match (state, event) {
+            (DarioState::Regular, Event::TakeDamage) if self.left > 1 => (
+                DarioState::Regular,
+                Self {
+                    left: self.left - 1,
+                    ..self
+                },
+            ),
+            (DarioState::Regular, Event::TakeDamage) => {
+                (DarioState::GameOver, Self { left: 0, ..self })
+            }
+            (DarioState::GameOver, Event::Revive) => (DarioState::Regular, Self::full(self.max)),
+            (state, event) => (transition(state, event), self),
+        }
+    }
+
+    /// Restores one heart, up to the maximum.
+    pub fn heal(self) -> Self {
+        Self {
+            left: self.left.saturating_add(1).min(self.max),
+            ..self
+        }
+    }
+}
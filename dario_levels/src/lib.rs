@@ -0,0 +1,280 @@
+//! # Dario levels — chunked obstacle generation
+//!
+//! A library of hand-authored obstacle [`Pattern`]s grouped into
+//! [`Biome`]s, and a [`Chunker`] that strings them together from a seed.
+//! Shared by `dash_core` (60 Hz) and `dash_zk` (30 Hz). Pieces are placed
+//! on a clock measured in pixels at the base scroll speed
+//! ([`CLOCK_PX_PER_SEC`]), not in ticks, so both sims spawn the same
+//! layout at the same moments and the contract can recompute a chunked
+//! schedule natively. As with the classic rolled gaps, pieces therefore
+//! sit further apart on screen as the scroll speeds up.
+//!
+//! The chunker draws from its own xorshift64* stream, salted from the run
+//! seed, so the sims' item rolls are independent of the chunk picks. The
+//! biome changes every [`CHUNKS_PER_BIOME`] chunks and the difficulty tier
+//! rises every [`CHUNKS_PER_TIER`] chunks up to [`MAX_TIER`]; each pick is a
+//! pattern of the current biome from the current tier or the one below.
+#![no_std]
+
+mod library;
+
+/// Chunks played before the biome changes.
+pub const CHUNKS_PER_BIOME: u32 = 6;
+/// Chunks played per difficulty tier.
+pub const CHUNKS_PER_TIER: u32 = 4;
+/// Hardest pattern tier.
+pub const MAX_TIER: u8 = 3;
+/// Chunk clock rate: the base scroll speed, px/s. A sim at `hz` ticks per
+/// second passes `ticks * CLOCK_PX_PER_SEC / hz` to [`Chunker::poll`].
+pub const CLOCK_PX_PER_SEC: u32 = 330;
+/// Clock at which the first chunk starts, px; about when the classic
+/// generators place their first obstacle.
+pub const FIRST_CHUNK_PX: u32 = 460;
+
+/// One obstacle of a pattern. Sizes are pixels, in the ranges the classic
+/// random rolls produce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece {
+    Barrel {
+        w: u8,
+        h: u8,
+    },
+    Pipe {
+        h: u8,
+    },
+    /// `lift` is the hover-center top edge above the ground line; `phase`
+    /// is the starting hover phase in 60 Hz ticks (`0..72`).
+    Bat {
+        lift: u8,
+        phase: u8,
+    },
+}
+
+/// A piece and its clock offset from the chunk start, px.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slot {
+    pub dx: u32,
+    pub piece: Piece,
+}
+
+/// A hand-authored chunk. `len` includes the run-out before the next chunk.
+#[derive(Clone, Copy, Debug)]
+pub struct Pattern {
+    pub name: &'static str,
+    pub tier: u8,
+    pub len: u32,
+    pub slots: &'static [Slot],
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Biome {
+    /// Barrels of every size.
+    Meadow = 0,
+    /// Pipes, with the odd barrel.
+    Sewer = 1,
+    /// Bats, guarded by pipes.
+    Belfry = 2,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 3] = [Biome::Meadow, Biome::Sewer, Biome::Belfry];
+
+    /// The biome's patterns, easiest first.
+    pub fn patterns(self) -> &'static [Pattern] {
+        match self {
+            Biome::Meadow => library::MEADOW,
+            Biome::Sewer => library::SEWER,
+            Biome::Belfry => library::BELFRY,
+        }
+    }
+}
+
+/// A [`Chunker`]'s complete state, for save files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkState {
+    pub rng: u64,
+    pub chunk: u32,
+    pub biome: u8,
+    pub pattern: u8,
+    pub start_px: u32,
+    pub next_slot: u8,
+}
+
+/// Deterministic chunk sequence for one run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunker {
+    rng: u64,
+    /// Index of the current chunk in the run.
+    chunk: u32,
+    biome: Biome,
+    pattern: u8,
+    /// Clock at which the current chunk starts, px.
+    start_px: u32,
+    /// Next slot of the current pattern to spawn.
+    next_slot: u8,
+}
+
+impl Chunker {
+    /// The chunk sequence for `seed`.
+    pub fn new(seed: u64) -> Self {
+        // Salted so the stream differs from the sims' own xorshift.
+        let mut chunker = Chunker {
+            rng: seed ^ 0x6c65_7665_6c73_2121,
+            chunk: 0,
+            biome: Biome::Meadow,
+            pattern: 0,
+            start_px: FIRST_CHUNK_PX,
+            next_slot: 0,
+        };
+        chunker.biome = Biome::ALL[chunker.below(3) as usize];
+        chunker.pick_pattern();
+        chunker
+    }
+
+    /// Current biome.
+    pub fn biome(&self) -> Biome {
+        self.biome
+    }
+
+    /// Current chunk's pattern.
+    pub fn pattern(&self) -> &'static Pattern {
+        &self.biome.patterns()[usize::from(self.pattern)]
+    }
+
+    /// Difficulty tier new chunks are drawn from.
+    pub fn tier(&self) -> u8 {
+        (self.chunk / CHUNKS_PER_TIER).min(u32::from(MAX_TIER)) as u8
+    }
+
+    /// Returns the next piece due by clock `clock_px` (see
+    /// [`CLOCK_PX_PER_SEC`]), moving on to new chunks as they are reached.
+    /// Call until it returns `None` each tick.
+    pub fn poll(&mut self, clock_px: u32) -> Option<Piece> {
+        loop {
+            let pattern = self.pattern();
+            if let Some(slot) = pattern.slots.get(usize::from(self.next_slot)) {
+                if clock_px < self.start_px + slot.dx {
+                    return None;
+                }
+                self.next_slot += 1;
+                return Some(slot.piece);
+            }
+            let end = self.start_px + pattern.len;
+            if clock_px < end {
+                return None;
+            }
+            self.start_px = end;
+            self.chunk += 1;
+            if self.chunk.is_multiple_of(CHUNKS_PER_BIOME) {
+                let step = 1 + self.below(2) as u8;
+                self.biome = Biome::ALL[usize::from((self.biome as u8 + step) % 3)];
+            }
+            self.pick_pattern();
+        }
+    }
+
+    pub fn state(&self) -> ChunkState {
+        ChunkState {
+            rng: self.rng,
+            chunk: self.chunk,
+            biome: self.biome as u8,
+            pattern: self.pattern,
+            start_px: self.start_px,
+            next_slot: self.next_slot,
+        }
+    }
+
+    /// Rebuilds a chunker from [`Chunker::state`]; `None` if the state
+    /// names no pattern or slot of the library.
+    pub fn from_state(state: ChunkState) -> Option<Chunker> {
+        let biome = *Biome::ALL.get(usize::from(state.biome))?;
+        let pattern = biome.patterns().get(usize::from(state.pattern))?;
+        if usize::from(state.next_slot) > pattern.slots.len() {
+            return None;
+        }
+        Some(Chunker {
+            rng: state.rng,
+            chunk: state.chunk,
+            biome,
+            pattern: state.pattern,
+            start_px: state.start_px,
+            next_slot: state.next_slot,
+        })
+    }
+
+    fn pick_pattern(&mut self) {
+        let tier = self.tier();
+        let fits = |p: &&Pattern| p.tier <= tier && p.tier + 1 >= tier;
+        let patterns = self.biome.patterns();
+        let n = patterns.iter().filter(fits).count() as u32;
+        let pick = self.below(n) as usize;
+        let (i, _) = patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| fits(p))
+            .nth(pick)
+            .unwrap();
+        self.pattern = i as u8;
+        self.next_slot = 0;
+    }
+
+    /// xorshift64*, uniform-ish in `0..n` (n > 0).
+    fn below(&mut self, n: u32) -> u32 {
+        let mut x = self.rng;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng = x;
+        (x.wrapping_mul(0x2545_f491_4f6c_dd1d) % u64::from(n)) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_tier_has_a_pattern_in_every_biome() {
+        for biome in Biome::ALL {
+            let patterns = biome.patterns();
+            for tier in 0..=MAX_TIER {
+                assert!(patterns.iter().any(|p| p.tier == tier), "{biome:?} {tier}");
+            }
+            for p in patterns {
+                assert!(p.slots.windows(2).all(|w| w[0].dx <= w[1].dx), "{}", p.name);
+                assert!(p.slots.last().unwrap().dx < p.len, "{}", p.name);
+            }
+        }
+    }
+
+    #[test]
+    fn chunks_are_deterministic_and_get_harder() {
+        let run = |seed: u64| {
+            let mut chunker = Chunker::new(seed);
+            let mut pieces = 0u32;
+            let mut biomes = [false; 3];
+            for px in (0..60_000).step_by(7) {
+                while chunker.poll(px).is_some() {
+                    pieces += 1;
+                }
+                biomes[chunker.biome() as usize] = true;
+            }
+            (chunker, pieces, biomes)
+        };
+        let (a, pieces, biomes) = run(42);
+        assert_eq!(a, run(42).0);
+        assert_ne!(a, run(43).0);
+        assert!(pieces > 50);
+        assert_eq!(biomes.iter().filter(|&&b| b).count(), 3);
+        assert_eq!(a.tier(), MAX_TIER);
+        assert!(a.pattern().tier + 1 >= MAX_TIER);
+
+        assert_eq!(Chunker::from_state(a.state()), Some(a));
+        let bad = ChunkState {
+            pattern: 200,
+            ..a.state()
+        };
+        assert_eq!(Chunker::from_state(bad), None);
+    }
+}
@@ -0,0 +1,108 @@
+//! The hand-authored patterns. Pieces closer than ~90 clock px are
+//! cleared in one jump; otherwise pieces sit at least 360 clock px (1.1 s)
+//! apart, enough to land and jump again. Higher tiers tighten the spacing
+//! and raise the obstacles.
+
+use crate::{Pattern, Piece, Slot};
+
+const fn barrel(dx: u32, w: u8, h: u8) -> Slot {
+    Slot {
+        dx,
+        piece: Piece::Barrel { w, h },
+    }
+}
+
+const fn pipe(dx: u32, h: u8) -> Slot {
+    Slot {
+        dx,
+        piece: Piece::Pipe { h },
+    }
+}
+
+const fn bat(dx: u32, lift: u8, phase: u8) -> Slot {
+    Slot {
+        dx,
+        piece: Piece::Bat { lift, phase },
+    }
+}
+
+pub(crate) const MEADOW: &[Pattern] = &[
+    Pattern {
+        name: "lone barrel",
+        tier: 0,
+        len: 760,
+        slots: &[barrel(0, 40, 50)],
+    },
+    Pattern {
+        name: "barrel steps",
+        tier: 1,
+        len: 1120,
+        slots: &[barrel(0, 36, 44), barrel(440, 44, 60)],
+    },
+    Pattern {
+        name: "barrel pair",
+        tier: 2,
+        len: 720,
+        slots: &[barrel(0, 34, 46), barrel(64, 40, 58)],
+    },
+    Pattern {
+        name: "orchard",
+        tier: 3,
+        len: 1040,
+        slots: &[barrel(0, 46, 70), barrel(380, 34, 42), barrel(440, 40, 62)],
+    },
+];
+
+pub(crate) const SEWER: &[Pattern] = &[
+    Pattern {
+        name: "lone pipe",
+        tier: 0,
+        len: 800,
+        slots: &[pipe(0, 90)],
+    },
+    Pattern {
+        name: "pipe and barrel",
+        tier: 1,
+        len: 1140,
+        slots: &[pipe(0, 96), barrel(440, 38, 52)],
+    },
+    Pattern {
+        name: "pipe run",
+        tier: 2,
+        len: 1020,
+        slots: &[pipe(0, 88), pipe(400, 104)],
+    },
+    Pattern {
+        name: "tall pipes",
+        tier: 3,
+        len: 1360,
+        slots: &[pipe(0, 112), pipe(380, 108), barrel(760, 50, 74)],
+    },
+];
+
+pub(crate) const BELFRY: &[Pattern] = &[
+    Pattern {
+        name: "lone bat",
+        tier: 0,
+        len: 760,
+        slots: &[bat(0, 120, 0)],
+    },
+    Pattern {
+        name: "bat and barrel",
+        tier: 1,
+        len: 1120,
+        slots: &[bat(0, 100, 0), barrel(440, 40, 50)],
+    },
+    Pattern {
+        name: "bat swarm",
+        tier: 2,
+        len: 1320,
+        slots: &[bat(0, 110, 0), bat(360, 140, 36), bat(720, 100, 18)],
+    },
+    Pattern {
+        name: "belfry gate",
+        tier: 3,
+        len: 1020,
+        slots: &[pipe(0, 92), bat(380, 130, 0), bat(420, 90, 36)],
+    },
+];
@@ -0,0 +1,305 @@
+//! # Dario replay files
+//!
+//! A single binary `.dario` format for recorded runs, shared by the web app
+//! (`dash_web`), `dash_zk`'s `export_input` and the RISC Zero `dash-prover`.
+//! A replay records which sim produced the trace, so a 60 Hz `dash_core`
+//! trace is never fed to the 30 Hz `dash_zk` sim or vice versa, and the
+//! hash of its config (`dash_core::SimConfig::hash`,
+//! `dash_zk::ZkSim::config_hash`) so it is replayed under the same rules.
+//!
+//! Layout (little-endian):
+//!
+//! ```text
+//! magic      4   b"DRIO"
+//! version    1   REPLAY_VERSION
+//! sim kind   1   0 = dash_core (60 Hz), 1 = dash_zk (30 Hz)
+//! flags      1   bit0 account present, bit1 run over
+//! seed       8
+//! config     8   hash of the sim config the trace was recorded under
+//! score      8
+//! ticks      4
+//! account    96  only when bit0 is set
+//! trace_len  4   unpacked ticks
+//! packed_len 4
+//! trace      packed_len bytes, run-length packed (see [`trace`])
+//! ```
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+pub mod trace;
+
+/// Replay file magic.
+pub const REPLAY_MAGIC: [u8; 4] = *b"DRIO";
+/// Current replay format version. Bump on any layout change.
+pub const REPLAY_VERSION: u8 = 1;
+/// Moonlight public account length (compressed BLS key bytes).
+pub const ACCOUNT_LEN: usize = 96;
+
+const FLAG_ACCOUNT: u8 = 1;
+const FLAG_OVER: u8 = 2;
+
+/// Which simulation a trace belongs to.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimKind {
+    /// 60 Hz `dash_core`, proven by the RISC Zero guest.
+    Core = 0,
+    /// 30 Hz `dash_zk`, proven by the in-browser circom circuit.
+    Zk = 1,
+}
+
+impl SimKind {
+    /// Longest trace the sim will replay: `MAX_TICKS` of the sim crate,
+    /// which tests there check against.
+    pub fn max_ticks(self) -> u32 {
+        match self {
+            SimKind::Core => 60 * 600,
+            SimKind::Zk => 30 * 120,
+        }
+    }
+}
+
+impl TryFrom<u8> for SimKind {
+    type Error = ReplayError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SimKind::Core),
+            1 => Ok(SimKind::Zk),
+            _ => Err(ReplayError::UnknownSimKind(value)),
+        }
+    }
+}
+
+/// Result the recording sim reported at the end of the trace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    pub score: u64,
+    pub ticks: u32,
+    pub over: bool,
+}
+
+/// Why a replay (or trace) could not be written or read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The input does not start with [`REPLAY_MAGIC`].
+    BadMagic,
+    /// The input was written by an unknown format version.
+    UnsupportedVersion(u8),
+    /// The sim kind byte names no known sim.
+    UnknownSimKind(u8),
+    /// The input ends before the encoding does.
+    Truncated,
+    /// Bytes follow the packed trace.
+    TrailingBytes,
+    /// The trace is longer than its sim can replay.
+    TraceTooLong,
+    /// A trace byte carries bits outside [`trace::TRACE_BITS`].
+    InvalidInput(u8),
+    /// The unpacked trace length disagrees with the header.
+    LengthMismatch,
+}
+
+/// A recorded run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    pub kind: SimKind,
+    pub seed: u64,
+    /// Hash of the sim config the trace was recorded under.
+    pub config_hash: u64,
+    /// Account the run is bound to, if any.
+    pub account: Option<[u8; ACCOUNT_LEN]>,
+    pub summary: RunSummary,
+    /// One input byte per tick.
+    pub trace: Vec<u8>,
+}
+
+impl Replay {
+    /// Serializes the replay into a fresh `.dario` byte buffer.
+    pub fn encode(&self) -> Result<Vec<u8>, ReplayError> {
+        if self.trace.len() > self.kind.max_ticks() as usize {
+            return Err(ReplayError::TraceTooLong);
+        }
+        let mut packed = Vec::new();
+        trace::encode(&self.trace, &mut packed)?;
+
+        let mut out = Vec::with_capacity(35 + ACCOUNT_LEN + 8 + packed.len());
+        out.extend_from_slice(&REPLAY_MAGIC);
+        out.push(REPLAY_VERSION);
+        out.push(self.kind as u8);
+        let mut flags = 0;
+        if self.account.is_some() {
+            flags |= FLAG_ACCOUNT;
+        }
+        if self.summary.over {
+            flags |= FLAG_OVER;
+        }
+        out.push(flags);
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&self.config_hash.to_le_bytes());
+        out.extend_from_slice(&self.summary.score.to_le_bytes());
+        out.extend_from_slice(&self.summary.ticks.to_le_bytes());
+        if let Some(account) = &self.account {
+            out.extend_from_slice(account);
+        }
+        out.extend_from_slice(&(self.trace.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(packed.len() as u32).to_le_bytes());
+        out.extend_from_slice(&packed);
+        Ok(out)
+    }
+
+    /// Parses a `.dario` byte buffer.
+    pub fn decode(bytes: &[u8]) -> Result<Replay, ReplayError> {
+        let mut r = Reader { buf: bytes };
+        if r.take(4).map_err(|_| ReplayError::BadMagic)? != REPLAY_MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        match r.u8()? {
+            REPLAY_VERSION => {}
+            v => return Err(ReplayError::UnsupportedVersion(v)),
+        }
+        let kind = SimKind::try_from(r.u8()?)?;
+        let flags = r.u8()?;
+        let seed = r.u64()?;
+        let config_hash = r.u64()?;
+        let summary = RunSummary {
+            score: r.u64()?,
+            ticks: r.u32()?,
+            over: flags & FLAG_OVER != 0,
+        };
+        let account = match flags & FLAG_ACCOUNT {
+            0 => None,
+            _ => Some(r.take(ACCOUNT_LEN)?.try_into().unwrap()),
+        };
+        let trace_len = r.u32()?;
+        if trace_len > kind.max_ticks() {
+            return Err(ReplayError::TraceTooLong);
+        }
+        let packed_len = r.u32()? as usize;
+        let packed = r.take(packed_len)?;
+        if !r.buf.is_empty() {
+            return Err(ReplayError::TrailingBytes);
+        }
+        let mut trace = Vec::with_capacity(trace_len as usize);
+        trace::decode(packed, &mut trace, trace_len as usize)?;
+        if trace.len() != trace_len as usize {
+            return Err(ReplayError::LengthMismatch);
+        }
+        Ok(Replay {
+            kind,
+            seed,
+            config_hash,
+            account,
+            summary,
+            trace,
+        })
+    }
+}
+
+/// True when `bytes` starts like a `.dario` file (any version).
+pub fn is_replay(bytes: &[u8]) -> bool {
+    bytes.starts_with(&REPLAY_MAGIC)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ReplayError> {
+        if self.buf.len() < n {
+            return Err(ReplayError::Truncated);
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn sample() -> Replay {
+        let mut trace = vec![0u8; 1200];
+        for (i, b) in trace.iter_mut().enumerate() {
+            *b = if i % 90 < 14 { 1 } else { 0 } | if i % 20 == 0 { 2 } else { 0 };
+        }
+        Replay {
+            kind: SimKind::Core,
+            seed: 42,
+            config_hash: 0x1234_5678_9abc_def0,
+            account: Some([7; ACCOUNT_LEN]),
+            summary: RunSummary {
+                score: 123,
+                ticks: 1200,
+                over: true,
+            },
+            trace,
+        }
+    }
+
+    #[test]
+    fn replays_roundtrip_with_and_without_account() {
+        let replay = sample();
+        let bytes = replay.encode().unwrap();
+        assert!(is_replay(&bytes));
+        assert!(bytes.len() < replay.trace.len() / 2);
+        assert_eq!(Replay::decode(&bytes).unwrap(), replay);
+
+        let anon = Replay {
+            kind: SimKind::Zk,
+            account: None,
+            ..sample()
+        };
+        assert_eq!(Replay::decode(&anon.encode().unwrap()).unwrap(), anon);
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let bytes = sample().encode().unwrap();
+        assert_eq!(Replay::decode(b"RUN"), Err(ReplayError::BadMagic));
+        assert_eq!(
+            Replay::decode(&bytes[..bytes.len() - 1]),
+            Err(ReplayError::Truncated)
+        );
+
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(Replay::decode(&extra), Err(ReplayError::TrailingBytes));
+
+        let mut future = bytes.clone();
+        future[4] = REPLAY_VERSION + 1;
+        assert_eq!(
+            Replay::decode(&future),
+            Err(ReplayError::UnsupportedVersion(REPLAY_VERSION + 1))
+        );
+
+        let mut kind = bytes;
+        kind[5] = 9;
+        assert_eq!(Replay::decode(&kind), Err(ReplayError::UnknownSimKind(9)));
+
+        let long = Replay {
+            kind: SimKind::Zk,
+            trace: vec![0; 3601],
+            ..sample()
+        };
+        assert_eq!(long.encode(), Err(ReplayError::TraceTooLong));
+    }
+}
@@ -0,0 +1,125 @@
+//! Run-length codec for input traces.
+//!
+//! A trace holds one input byte per tick and only [`TRACE_BITS`] are ever
+//! set, so each run of equal bytes packs into a single header byte
+//! `more << 7 | (n & 0xF) << 3 | input`, where `n` is the run length minus
+//! one; when `more` is set the remaining `n >> 4` follows as a LEB128
+//! varint. A 36,000-tick idle run packs into 3 bytes.
+
+use alloc::vec::Vec;
+
+use crate::ReplayError;
+
+/// Input bits a trace byte may carry (jump, fire, use).
+pub const TRACE_BITS: u8 = 0x07;
+
+/// Appends the packed form of `trace` to `out`.
+///
+/// Fails with [`ReplayError::InvalidInput`] on a byte outside
+/// [`TRACE_BITS`]; mask traces with the sim's `INPUT_MASK` first.
+pub fn encode(trace: &[u8], out: &mut Vec<u8>) -> Result<(), ReplayError> {
+    let mut rest = trace;
+    while let Some(&input) = rest.first() {
+        if input & !TRACE_BITS != 0 {
+            return Err(ReplayError::InvalidInput(input));
+        }
+        let len = rest.iter().take_while(|&&b| b == input).count();
+        rest = &rest[len..];
+
+        let n = len as u64 - 1;
+        let more = n >> 4;
+        out.push(input | ((n & 0xF) as u8) << 3 | u8::from(more != 0) << 7);
+        let mut v = more;
+        while v != 0 {
+            let byte = (v & 0x7F) as u8;
+            v >>= 7;
+            out.push(if v != 0 { byte | 0x80 } else { byte });
+        }
+    }
+    Ok(())
+}
+
+/// Appends the trace unpacked from `packed` to `out`, refusing to grow it
+/// past `max_len` ticks so hostile input cannot force large allocations.
+pub fn decode(packed: &[u8], out: &mut Vec<u8>, max_len: usize) -> Result<(), ReplayError> {
+    let mut bytes = packed.iter();
+    while let Some(&head) = bytes.next() {
+        let mut n = u64::from(head >> 3 & 0xF);
+        if head & 0x80 != 0 {
+            let mut more = 0u64;
+            let mut shift = 0;
+            loop {
+                let &byte = bytes.next().ok_or(ReplayError::Truncated)?;
+                if shift > 28 {
+                    return Err(ReplayError::TraceTooLong);
+                }
+                more |= u64::from(byte & 0x7F) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            n |= more << 4;
+        }
+        // Compare in u64: `n` can exceed `usize` on 32-bit targets.
+        if n >= max_len.saturating_sub(out.len()) as u64 {
+            return Err(ReplayError::TraceTooLong);
+        }
+        out.resize(out.len() + n as usize + 1, head & TRACE_BITS);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn roundtrip(trace: &[u8]) -> usize {
+        let mut packed = Vec::new();
+        encode(trace, &mut packed).unwrap();
+        let mut unpacked = Vec::new();
+        decode(&packed, &mut unpacked, trace.len()).unwrap();
+        assert_eq!(unpacked, trace);
+        packed.len()
+    }
+
+    #[test]
+    fn runs_pack_and_unpack_exactly() {
+        assert_eq!(roundtrip(&[]), 0);
+        assert_eq!(roundtrip(&[1, 1, 0, 2, 2, 2, 3]), 4);
+        assert_eq!(roundtrip(&[0; 16]), 1);
+        assert_eq!(roundtrip(&[0; 17]), 2);
+        assert_eq!(roundtrip(&vec![0; 36_000]), 3);
+
+        let mut trace = vec![0u8; 3600];
+        for (i, b) in trace.iter_mut().enumerate() {
+            *b = [0, 0, 1, 1, 1, 0, 2, 4][i / 13 % 8];
+        }
+        assert!(roundtrip(&trace) < trace.len() / 10);
+    }
+
+    #[test]
+    fn rejects_unknown_bits_and_oversized_output() {
+        assert_eq!(
+            encode(&[0, 8], &mut Vec::new()),
+            Err(ReplayError::InvalidInput(8))
+        );
+        let mut packed = Vec::new();
+        encode(&[0; 100], &mut packed).unwrap();
+        assert_eq!(
+            decode(&packed, &mut Vec::new(), 99),
+            Err(ReplayError::TraceTooLong)
+        );
+        assert_eq!(
+            decode(&packed[..1], &mut Vec::new(), 100),
+            Err(ReplayError::Truncated)
+        );
+        // A run of about 2^39 ticks, which must not wrap on 32-bit targets.
+        let huge = [0xF8, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F];
+        assert_eq!(
+            decode(&huge, &mut Vec::new(), 100),
+            Err(ReplayError::TraceTooLong)
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! # Dario view — render views of the sims' entities
+//!
+//! `dash_core::view` and `dash_zk::view` re-export these types, so
+//! renderers and `dash_web` draw both sims from one shape. Positions and
+//! sizes are in pixels, and ids are stable across frames and never reused
+//! within a run; each sim documents how it assigns them. Bosses and their
+//! shots only appear in `dash_core` runs.
+#![no_std]
+
+/// `i32`s per [`Entity::record`].
+pub const RECORD_LEN: usize = 6;
+
+/// An obstacle (the sim's `KIND_*`). `y` is the ground anchor (bottom edge) for
+/// barrels, pipes and ceiling spikes (which reach up to `y - h == 0`) and
+/// the top edge for bats and platforms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Obstacle {
+    pub id: u32,
+    pub kind: i32,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+/// A collectible (`ITEM_*`); `x`/`y` is the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Item {
+    pub id: u32,
+    pub kind: i32,
+    pub x: i32,
+    pub y: i32,
+    pub size: i32,
+}
+
+/// A fireball; `x`/`y` is the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fireball {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub size: i32,
+}
+
+/// A coin; `x`/`y` is the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Coin {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub size: i32,
+}
+
+/// A boss (`dash_core` only); `x`/`y` is the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Boss {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub hp: u8,
+    pub max_hp: u8,
+}
+
+/// A shot thrown by a boss (`dash_core` only); `x`/`y` is the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BossShot {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub size: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Entity {
+    Obstacle(Obstacle),
+    Item(Item),
+    Fireball(Fireball),
+    Coin(Coin),
+    Boss(Boss),
+    BossShot(BossShot),
+}
+
+impl Entity {
+    pub fn id(&self) -> u32 {
+        match self {
+            Entity::Obstacle(o) => o.id,
+            Entity::Item(i) => i.id,
+            Entity::Fireball(f) => f.id,
+            Entity::Coin(c) => c.id,
+            Entity::Boss(b) => b.id,
+            Entity::BossShot(s) => s.id,
+        }
+    }
+
+    /// The flat `[entity_type, kind, x, y, w, h]` snapshot record, where
+    /// entity_type is 0 = obstacle, 1 = item, 2 = fireball, 3 = coin,
+    /// 4 = boss (with its hit points as the kind), 5 = boss shot.
+    pub fn record(&self) -> [i32; RECORD_LEN] {
+        match *self {
+            Entity::Obstacle(o) => [0, o.kind, o.x, o.y, o.w, o.h],
+            Entity::Item(i) => [1, i.kind, i.x, i.y, i.size, i.size],
+            Entity::Fireball(f) => [2, 0, f.x, f.y, f.size, f.size],
+            Entity::Coin(c) => [3, 0, c.x, c.y, c.size, c.size],
+            Entity::Boss(b) => [4, i32::from(b.hp), b.x, b.y, b.w, b.h],
+            Entity::BossShot(s) => [5, 0, s.x, s.y, s.size, s.size],
+        }
+    }
+}
+
+/// Writes [`Entity::record`]s into `out` until it is full, returning the
+/// number of `i32` values written.
+pub fn write_records(entities: impl Iterator<Item = Entity>, out: &mut [i32]) -> usize {
+    let mut n = 0;
+    for (entity, chunk) in entities.zip(out.chunks_exact_mut(RECORD_LEN)) {
+        chunk.copy_from_slice(&entity.record());
+        n += RECORD_LEN;
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_records_stops_at_a_full_buffer() {
+        let coin = |id| {
+            Entity::Coin(Coin {
+                id,
+                x: 10,
+                y: 20,
+                size: 8,
+            })
+        };
+        let mut out = [0i32; RECORD_LEN * 2 + 3];
+        assert_eq!(write_records((0..5).map(coin), &mut out), RECORD_LEN * 2);
+        assert_eq!(out[RECORD_LEN..RECORD_LEN * 2], [3, 0, 10, 20, 8, 8]);
+        assert_eq!(out[RECORD_LEN * 2..], [0; 3]);
+    }
+}
@@ -0,0 +1,363 @@
+//! # Dash achievements — deterministic in-run goals
+//!
+//! Watches a `dash_core::Sim` or `dash_zk::ZkSim` tick by tick and unlocks
+//! [`Achievement`]s. The rules only read what the sims report after each
+//! tick (their events, or `dash_zk`'s witness logs, plus score, form and
+//! grounding), so the unlocked set is a pure function of the seed and the
+//! input trace: [`evaluate_core`] and [`evaluate_zk`] replay a trace and
+//! agree with what a live [`Tracker`] showed in-game, and a prover that
+//! replays the trace could attest to them.
+//!
+//! Both sims feed the same [`Frame`]s, so every achievement means the same
+//! on either; durations are in seconds and scaled by the sim's tick rate.
+#![no_std]
+
+use dario_fsm::DarioState;
+use dash_core::GameEvent;
+
+/// An in-run goal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    /// Survive 60 s without jumping twice in a row: no jump may start on
+    /// the first tick back on the ground after a landing. Ranked `dash_zk`
+    /// runs usually hit the score cap sooner.
+    SteadyFeet,
+    /// Kill 3 bats.
+    BatHunter,
+    /// Reach Cape form, then Fire form later in the same run.
+    CapeThenFire,
+    /// Score 1000 points in a row without taking damage.
+    Untouchable,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::SteadyFeet,
+        Achievement::BatHunter,
+        Achievement::CapeThenFire,
+        Achievement::Untouchable,
+    ];
+
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::SteadyFeet => "Steady Feet",
+            Achievement::BatHunter => "Bat Hunter",
+            Achievement::CapeThenFire => "Cape Then Fire",
+            Achievement::Untouchable => "Untouchable",
+        }
+    }
+}
+
+const STEADY_SECS: u32 = 60;
+const BAT_KILLS: u32 = 3;
+const UNTOUCHED_POINTS: u64 = 1000;
+
+/// A set of unlocked achievements, as [`Achievement::bit`]s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Unlocked(pub u32);
+
+impl Unlocked {
+    pub fn contains(self, a: Achievement) -> bool {
+        self.0 & a.bit() != 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Achievement> {
+        Achievement::ALL
+            .into_iter()
+            .filter(move |&a| self.contains(a))
+    }
+}
+
+/// What a sim reports after one tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub tick: u32,
+    pub score: u64,
+    pub form: DarioState,
+    pub grounded: bool,
+    /// A jump started this tick.
+    pub jumped: bool,
+    pub bats_killed: u32,
+    /// Damage taken this tick (a touch while invulnerable is not).
+    pub damaged: bool,
+}
+
+/// Evaluates the achievements over a run, one [`Frame`] per tick.
+#[derive(Clone, Debug)]
+pub struct Tracker {
+    tick_hz: u32,
+    unlocked: Unlocked,
+    grounded: bool,
+    landed_at: Option<u32>,
+    chained_jump: bool,
+    bats: u32,
+    had_cape: bool,
+    /// Score at the last damage, or 0.
+    safe_since: u64,
+    /// `dash_zk` log entries already turned into frames.
+    zk_seen: [usize; 3],
+}
+
+impl Tracker {
+    /// A tracker for a sim running at `tick_hz`.
+    pub fn new(tick_hz: u32) -> Self {
+        Tracker {
+            tick_hz,
+            unlocked: Unlocked::default(),
+            grounded: true,
+            landed_at: None,
+            chained_jump: false,
+            bats: 0,
+            had_cape: false,
+            safe_since: 0,
+            zk_seen: [0; 3],
+        }
+    }
+
+    /// Feeds the next tick; returns the achievements it unlocked.
+    pub fn step(&mut self, frame: &Frame) -> Unlocked {
+        let before = self.unlocked;
+        let alive = frame.form != DarioState::GameOver;
+        if frame.jumped {
+            self.chained_jump |= self.landed_at == Some(frame.tick - 1);
+        }
+        if frame.grounded && !self.grounded {
+            self.landed_at = Some(frame.tick);
+        }
+        self.grounded = frame.grounded;
+        if alive && !self.chained_jump && frame.tick >= STEADY_SECS * self.tick_hz {
+            self.unlock(Achievement::SteadyFeet);
+        }
+
+        self.bats += frame.bats_killed;
+        if self.bats >= BAT_KILLS {
+            self.unlock(Achievement::BatHunter);
+        }
+
+        match frame.form {
+            DarioState::Cape => self.had_cape = true,
+            DarioState::Fire if self.had_cape => self.unlock(Achievement::CapeThenFire),
+            _ => {}
+        }
+
+        if frame.damaged {
+            self.safe_since = frame.score;
+        } else if alive && frame.score - self.safe_since >= UNTOUCHED_POINTS {
+            self.unlock(Achievement::Untouchable);
+        }
+        Unlocked(self.unlocked.0 & !before.0)
+    }
+
+    fn unlock(&mut self, a: Achievement) {
+        self.unlocked.0 |= a.bit();
+    }
+
+    /// Everything unlocked so far.
+    pub fn unlocked(&self) -> Unlocked {
+        self.unlocked
+    }
+
+    /// Feeds the tick `sim` just ran, draining its events. Call after every
+    /// `tick`. A game that also needs the events should build the
+    /// [`Frame`] itself and call [`Tracker::step`].
+    pub fn watch_core(&mut self, sim: &mut dash_core::Sim) -> Unlocked {
+        let mut frame = Frame {
+            tick: sim.ticks(),
+            score: sim.score(),
+            form: DarioState::ALL[sim.form() as usize],
+            grounded: sim.grounded(),
+            jumped: false,
+            bats_killed: 0,
+            damaged: false,
+        };
+        for event in sim.drain_events() {
+            match event {
+                GameEvent::JumpStarted => frame.jumped = true,
+                GameEvent::ObstacleKilled {
+                    kind: dash_core::KIND_BAT,
+                } => frame.bats_killed += 1,
+                GameEvent::Damaged => frame.damaged = true,
+                _ => {}
+            }
+        }
+        self.step(&frame)
+    }
+
+    /// Feeds the tick `sim` just ran, from its witness logs. Call after
+    /// every `tick`.
+    pub fn watch_zk(&mut self, sim: &dash_zk::ZkSim) -> Unlocked {
+        let [jumps, kills, damages] = &mut self.zk_seen;
+        let frame = Frame {
+            tick: sim.ticks(),
+            score: sim.score(),
+            form: DarioState::ALL[sim.form() as usize],
+            grounded: sim.grounded(),
+            jumped: sim.jump_count > *jumps,
+            bats_killed: sim.kills[*kills..sim.kill_count]
+                .iter()
+                .filter(|k| k.target_class == dash_zk::TARGET_BAT)
+                .count() as u32,
+            damaged: sim.damages[*damages..sim.damage_count]
+                .iter()
+                .any(|d| !d.invuln_touch),
+        };
+        (*jumps, *kills, *damages) = (sim.jump_count, sim.kill_count, sim.damage_count);
+        self.step(&frame)
+    }
+}
+
+/// Replays `trace` from `seed` like `dash_core::replay` and returns the
+/// achievements the run unlocked.
+pub fn evaluate_core(seed: u64, trace: &[u8]) -> Unlocked {
+    let mut sim = dash_core::Sim::new(seed);
+    let mut tracker = Tracker::new(dash_core::TICK_HZ);
+    for &input in trace.iter().take(dash_core::MAX_TICKS as usize) {
+        if sim.over() {
+            break;
+        }
+        sim.tick(input);
+        tracker.watch_core(&mut sim);
+    }
+    tracker.unlocked()
+}
+
+/// Replays `trace` from `seed` like `dash_zk::replay` and returns the
+/// achievements the run unlocked.
+pub fn evaluate_zk(seed: u64, trace: &[u8]) -> Unlocked {
+    let mut sim = dash_zk::ZkSim::new(seed);
+    let mut tracker = Tracker::new(dash_zk::TICK_HZ);
+    for &input in trace {
+        if sim.over() || sim.ticks() >= dash_zk::MAX_TICKS {
+            break;
+        }
+        sim.tick(input);
+        tracker.watch_zk(&sim);
+    }
+    tracker.unlocked()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(tick: u32) -> Frame {
+        Frame {
+            tick,
+            score: 0,
+            form: DarioState::Regular,
+            grounded: true,
+            jumped: false,
+            bats_killed: 0,
+            damaged: false,
+        }
+    }
+
+    #[test]
+    fn rules_follow_the_frames() {
+        let mut t = Tracker::new(30);
+        // A jump on the tick after landing is back to back; Steady Feet
+        // is lost.
+        t.step(&Frame {
+            jumped: true,
+            grounded: false,
+            ..frame(1)
+        });
+        t.step(&frame(20));
+        t.step(&Frame {
+            jumped: true,
+            grounded: false,
+            ..frame(21)
+        });
+        assert_eq!(t.step(&frame(60 * 30)), Unlocked(0));
+        // Cape, then Fire.
+        t.step(&Frame {
+            form: DarioState::Fire,
+            ..frame(1801)
+        });
+        t.step(&Frame {
+            form: DarioState::Cape,
+            ..frame(1802)
+        });
+        let new = t.step(&Frame {
+            form: DarioState::Fire,
+            bats_killed: 3,
+            ..frame(1803)
+        });
+        assert_eq!(
+            new,
+            Unlocked(Achievement::CapeThenFire.bit() | Achievement::BatHunter.bit())
+        );
+        // Damage restarts the 1000 points.
+        t.step(&Frame {
+            score: 900,
+            damaged: true,
+            ..frame(1804)
+        });
+        t.step(&Frame {
+            score: 1899,
+            ..frame(1805)
+        });
+        assert!(!t.unlocked().contains(Achievement::Untouchable));
+        t.step(&Frame {
+            score: 1900,
+            ..frame(1806)
+        });
+        assert!(t.unlocked().contains(Achievement::Untouchable));
+    }
+
+    #[test]
+    fn steady_feet_takes_a_minute_without_back_to_back_jumps() {
+        // Jump every `every` ticks, 40 ticks in the air: landing at phase
+        // 41, so a 42-tick cycle jumps again on the first tick down.
+        for (every, steady) in [(90, true), (43, true), (42, false)] {
+            let mut t = Tracker::new(60);
+            for tick in 1..=3600 {
+                let phase = tick % every;
+                let new = t.step(&Frame {
+                    jumped: phase == 0,
+                    grounded: phase > 40,
+                    ..frame(tick)
+                });
+                assert_eq!(
+                    new.contains(Achievement::SteadyFeet),
+                    steady && tick == 3600,
+                    "every {every}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn solved_runs_unlock_the_same_set_live_and_replayed() {
+        use dash_solver::{solve, SolverConfig};
+
+        let zk = solve(&dash_zk::ZkSim::new(1), &SolverConfig::default());
+        let mut sim = dash_zk::ZkSim::new(1);
+        let mut live = Tracker::new(dash_zk::TICK_HZ);
+        for &input in &zk.trace {
+            sim.tick(input);
+            live.watch_zk(&sim);
+        }
+        assert_eq!(live.unlocked(), evaluate_zk(1, &zk.trace));
+        assert_eq!(live.unlocked(), Unlocked(Achievement::CapeThenFire.bit()));
+
+        let config = SolverConfig {
+            beam_width: 16,
+            decision_hz: 3,
+            target_score: 1700,
+        };
+        let core = solve(&dash_core::Sim::new(9), &config);
+        let unlocked = evaluate_core(9, &core.trace);
+        assert_eq!(
+            unlocked,
+            Unlocked(Achievement::CapeThenFire.bit() | Achievement::Untouchable.bit())
+        );
+        // Idling dies early and unlocks nothing.
+        assert_eq!(evaluate_core(9, &[0; 600]), Unlocked(0));
+        assert_eq!(evaluate_zk(1, &[0; 300]), Unlocked(0));
+    }
+}
@@ -0,0 +1,185 @@
+//! Physics, difficulty and rule settings for [`crate::Sim`].
+//!
+//! [`SimConfig::NORMAL`] holds the values the RISC Zero guest replays and is
+//! the only ranked config. Any other config, including the presets and the
+//! opt-in rule variants (form expiry, reserve, hearts, hazards, coins,
+//! chunks, bosses), plays unranked.
+//! [`SimConfig::hash`] identifies a config in replay files.
+
+use crate::desync::mix;
+use crate::MAX_TICKS;
+
+/// Everything that tunes a run. Velocities and accelerations are fp/tick,
+/// durations are ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimConfig {
+    pub gravity: i32,
+    pub jump_v: i32,
+    /// Jump velocity in Super form.
+    pub super_jump_v: i32,
+    /// Fall speed cap while gliding in Cape form.
+    pub glide_fall_cap: i32,
+    /// Scroll speed at tick 0.
+    pub base_speed: i32,
+    pub max_speed: i32,
+    /// Scroll speed gained per 100 ticks.
+    pub speed_ramp: i32,
+    /// Obstacle gaps are `gap_min + below(gap_range)` ticks at base speed,
+    /// scaled to the current speed, plus `gap_pad` ticks. Ranges must be
+    /// positive (see [`SimConfig::validate`]).
+    pub gap_min: u32,
+    pub gap_range: u32,
+    pub gap_pad: u32,
+    /// Item gaps are `item_gap_min + below(item_gap_range)` ticks.
+    pub item_gap_min: u32,
+    pub item_gap_range: u32,
+    pub invuln_ticks: u32,
+    pub fireball_cooldown: u32,
+    /// Power-up lifetime; `None` keeps forms until damage.
+    pub form_expiry: Option<u32>,
+    /// One-slot reserve deployed by `INPUT_USE`.
+    pub reserve: bool,
+    /// Hearts mode with this many hearts; `None` keeps one-hit rules.
+    pub hearts: Option<u8>,
+    /// Ceiling spikes and moving platforms join the obstacle rolls.
+    pub hazards: bool,
+    /// Coin arcs spawn and each coin scores `COIN_SCORE`.
+    pub coins: bool,
+    /// Obstacles come from `dario_levels` chunks and biomes instead of
+    /// independent rolls (the hazard kinds are then never spawned).
+    pub chunks: bool,
+    /// Boss encounters at fixed distances.
+    pub bosses: bool,
+}
+
+impl SimConfig {
+    /// The classic, ranked rules.
+    pub const NORMAL: SimConfig = SimConfig {
+        gravity: 185,        // 2600 px/s²
+        jump_v: -3925,       // -920 px/s
+        super_jump_v: -4608, // -1080 px/s
+        glide_fall_cap: 640, // 150 px/s
+        base_speed: 1408,    // 330 px/s
+        max_speed: 3243,     // 760 px/s
+        speed_ramp: 64,      // +0.15 px/s per tick
+        gap_min: 45,
+        gap_range: 55,
+        gap_pad: 15,
+        item_gap_min: 132,
+        item_gap_range: 193,
+        invuln_ticks: 78,      // 1.3 s
+        fireball_cooldown: 27, // 0.45 s
+        form_expiry: None,
+        reserve: false,
+        hearts: None,
+        hazards: false,
+        coins: false,
+        chunks: false,
+        bosses: false,
+    };
+
+    /// Slower ramp, wider gaps, more frequent items and longer
+    /// invulnerability.
+    pub const EASY: SimConfig = SimConfig {
+        max_speed: 2816, // 660 px/s
+        speed_ramp: 40,
+        gap_min: 60,
+        gap_range: 60,
+        gap_pad: 20,
+        item_gap_min: 100,
+        item_gap_range: 150,
+        invuln_ticks: 120,
+        ..SimConfig::NORMAL
+    };
+
+    /// Faster ramp, tighter gaps and shorter invulnerability.
+    pub const HARD: SimConfig = SimConfig {
+        max_speed: 3669, // 860 px/s
+        speed_ramp: 96,
+        gap_min: 36,
+        gap_range: 45,
+        gap_pad: 12,
+        item_gap_min: 180,
+        item_gap_range: 240,
+        invuln_ticks: 54,
+        ..SimConfig::NORMAL
+    };
+
+    /// The difficulty presets, easiest first.
+    pub const PRESETS: [SimConfig; 3] = [SimConfig::EASY, SimConfig::NORMAL, SimConfig::HARD];
+
+    /// Checks that a run under this config can play to `MAX_TICKS`.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.gap_range == 0 || self.item_gap_range == 0 {
+            return Err(ConfigError::EmptyGapRange);
+        }
+        let speed_positive = [0, MAX_TICKS].into_iter().all(|t| {
+            let s = i64::from(self.base_speed) + i64::from(t) * i64::from(self.speed_ramp) / 100;
+            i32::try_from(s).is_ok() && s.min(i64::from(self.max_speed)) > 0
+        });
+        if !speed_positive {
+            return Err(ConfigError::StalledSpeed);
+        }
+        if self.hearts == Some(0) {
+            return Err(ConfigError::NoHearts);
+        }
+        Ok(())
+    }
+
+    /// True for the one config proofs and the leaderboard accept.
+    pub fn ranked(&self) -> bool {
+        *self == SimConfig::NORMAL
+    }
+
+    /// Stable 64-bit identifier of every field, recorded in replays.
+    pub fn hash(&self) -> u64 {
+        let words = [
+            self.gravity as u32 as u64,
+            self.jump_v as u32 as u64,
+            self.super_jump_v as u32 as u64,
+            self.glide_fall_cap as u32 as u64,
+            self.base_speed as u32 as u64,
+            self.max_speed as u32 as u64,
+            self.speed_ramp as u32 as u64,
+            u64::from(self.gap_min),
+            u64::from(self.gap_range),
+            u64::from(self.gap_pad),
+            u64::from(self.item_gap_min),
+            u64::from(self.item_gap_range),
+            u64::from(self.invuln_ticks),
+            u64::from(self.fireball_cooldown),
+            self.form_expiry.map_or(u64::MAX, u64::from),
+            u64::from(self.reserve),
+            self.hearts.map_or(u64::MAX, u64::from),
+            u64::from(self.hazards),
+            u64::from(self.coins),
+            u64::from(self.chunks),
+            u64::from(self.bosses),
+        ];
+        words.into_iter().fold(0x6461_7269_6f63_6667, mix)
+    }
+
+    /// The preset whose [`SimConfig::hash`] is `hash`, for replaying files
+    /// recorded under a preset.
+    pub fn preset_by_hash(hash: u64) -> Option<SimConfig> {
+        SimConfig::PRESETS.into_iter().find(|c| c.hash() == hash)
+    }
+}
+
+/// Why a [`SimConfig`] cannot be played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// `gap_range` or `item_gap_range` is 0.
+    EmptyGapRange,
+    /// The scroll speed (linear in the tick, capped at `max_speed`) is not
+    /// positive, or overflows, at some tick up to `MAX_TICKS`.
+    StalledSpeed,
+    /// Hearts mode with no hearts.
+    NoHearts,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig::NORMAL
+    }
+}
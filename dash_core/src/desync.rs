@@ -0,0 +1,116 @@
+//! Desync detection for [`Sim`]: [`Sim::hash_states`] turns on the
+//! rolling per-tick state hash and the shared [`dario_desync`] recorder
+//! bisects two runs to the first tick where they differ.
+
+use crate::Sim;
+pub use dario_desync::{first_divergence, mix, record_checkpoints, Checkpoints, HashedSim};
+
+impl HashedSim for Sim {
+    fn tick(&mut self, input: u8) {
+        Sim::tick(self, input)
+    }
+
+    fn ticks(&self) -> u32 {
+        Sim::ticks(self)
+    }
+
+    fn over(&self) -> bool {
+        Sim::over(self)
+    }
+
+    fn state_hash(&self) -> u64 {
+        Sim::state_hash(self)
+    }
+
+    fn hash_states(&mut self) {
+        Sim::hash_states(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{INPUT_FIRE, INPUT_JUMP};
+
+    fn trace() -> [u8; 3000] {
+        let mut t = [0u8; 3000];
+        for (i, b) in t.iter_mut().enumerate() {
+            if i % 40 < 10 {
+                *b |= INPUT_JUMP;
+            }
+            if i % 9 == 0 {
+                *b |= INPUT_FIRE;
+            }
+        }
+        t
+    }
+
+    #[test]
+    fn first_divergence_bisects_to_the_tampered_tick() {
+        let trace = trace();
+        let mut a = [0u64; 3000];
+        let n = record_checkpoints(Sim::new(42), &trace, 1, &mut a);
+        let mut b = [0u64; 3000];
+        assert_eq!(record_checkpoints(Sim::new(42), &trace, 1, &mut b), n);
+        assert_eq!(first_divergence(1, &a[..n], &b[..n]), None);
+
+        let mut tampered = trace;
+        tampered[99] ^= INPUT_JUMP;
+        let m = record_checkpoints(Sim::new(42), &tampered, 1, &mut b);
+        assert_eq!(first_divergence(1, &a[..n], &b[..m]), Some(100));
+
+        let mut coarse_a = [0u64; 64];
+        let mut coarse_b = [0u64; 64];
+        let na = record_checkpoints(Sim::new(42), &trace, 64, &mut coarse_a);
+        let nb = record_checkpoints(Sim::new(42), &tampered, 64, &mut coarse_b);
+        assert_eq!(
+            first_divergence(64, &coarse_a[..na], &coarse_b[..nb]),
+            Some(128)
+        );
+    }
+
+    #[test]
+    fn different_seeds_diverge_on_the_first_tick() {
+        let mut a = [0u64; 4];
+        let mut b = [0u64; 4];
+        record_checkpoints(Sim::new(1), &[0; 4], 1, &mut a);
+        record_checkpoints(Sim::new(2), &[0; 4], 1, &mut b);
+        assert_eq!(first_divergence(1, &a, &b), Some(1));
+    }
+
+    #[test]
+    fn hash_covers_entity_ids_and_sizes() {
+        let mut sim = Sim::new(3);
+        sim.hash_states();
+        while !sim.obstacles.iter().any(|o| o.active) {
+            sim.tick(0);
+        }
+        let tampers: [fn(&mut Sim); 3] = [
+            |s| s.obstacles.iter_mut().find(|o| o.active).unwrap().id += 1,
+            |s| s.obstacles.iter_mut().find(|o| o.active).unwrap().w += 1,
+            |s| s.next_id += 1,
+        ];
+        let mut clean = sim.clone();
+        clean.tick(0);
+        for tamper in tampers {
+            let mut other = sim.clone();
+            tamper(&mut other);
+            other.tick(0);
+            assert_ne!(other.state_hash(), clean.state_hash());
+        }
+    }
+
+    #[test]
+    fn state_hashing_is_opt_in() {
+        let mut plain = Sim::new(7);
+        let mut hashed = Sim::new(7);
+        hashed.hash_states();
+        for _ in 0..30 {
+            plain.tick(INPUT_JUMP);
+            hashed.tick(INPUT_JUMP);
+        }
+        assert_eq!(plain.state_hash(), 0);
+        assert_ne!(hashed.state_hash(), 0);
+        assert_eq!(plain.score(), hashed.score());
+    }
+}
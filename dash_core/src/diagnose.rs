@@ -0,0 +1,217 @@
+//! Diagnostic replays: how and why a run ended.
+//!
+//! [`replay`](crate::replay) only returns a [`RunResult`](crate::RunResult). [`diagnose`]
+//! replays the same trace and also reports the [`Ending`] (for a death, the
+//! obstacle or boss shot and both hitboxes), every form change, the score
+//! by source and the slot tables that ran out along the way (see
+//! [`Capacity`]). Used to settle disputed deaths and to explain traces
+//! that fail to prove. The [`Diagnosis`] has the same shape as
+//! `dash_zk`'s; only the [`Death`] details differ.
+
+use crate::{
+    Aabb, Capacity, GameEvent, ScoreBreakdown, Sim, BOSS_SHOT_SIZE, FP, KIND_PLATFORM,
+    MAX_BOSS_SHOTS, MAX_OBSTACLES, MAX_TICKS,
+};
+use dario_diagnose::{FirstTicks, FormLog};
+pub use dario_diagnose::{FormChange, MAX_FORM_CHANGES};
+use dario_fsm::DarioState;
+
+/// An axis-aligned box in fp, top-left corner plus size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hitbox {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Hitbox {
+    pub fn overlaps(&self, other: &Hitbox) -> bool {
+        self.x < other.x + other.w
+            && self.x + self.w > other.x
+            && self.y < other.y + other.h
+            && self.y + self.h > other.y
+    }
+}
+
+impl From<Aabb> for Hitbox {
+    fn from(b: Aabb) -> Self {
+        Hitbox {
+            x: b.x,
+            y: b.y,
+            w: b.w,
+            h: b.h,
+        }
+    }
+}
+
+/// What dealt the final hit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Killer {
+    /// The obstacle in slot `slot` (`KIND_*`), with its entity id.
+    Obstacle { slot: usize, id: u32, kind: i32 },
+    /// A boss shot, with its entity id.
+    BossShot { slot: usize, id: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Death {
+    pub tick: u32,
+    pub killer: Killer,
+    /// Dario's and the killer's hitboxes on the fatal tick.
+    pub player: Hitbox,
+    pub hitbox: Hitbox,
+}
+
+/// How the run ended. This sim has no score cap, so never
+/// [`Ending::ScoreCap`].
+pub type Ending = dario_diagnose::Ending<Death>;
+
+#[derive(Clone, Debug)]
+pub struct Diagnosis {
+    pub score: u64,
+    pub ticks: u32,
+    pub over: bool,
+    pub ending: Ending,
+    pub breakdown: ScoreBreakdown,
+    forms: FormLog,
+    exhausted: FirstTicks<{ Capacity::ALL.len() }>,
+}
+
+impl Diagnosis {
+    /// Form changes in order, at most [`MAX_FORM_CHANGES`].
+    pub fn forms(&self) -> &[FormChange] {
+        self.forms.as_slice()
+    }
+
+    /// Each slot table that ran out, with the first tick it did, in
+    /// [`Capacity::ALL`] order.
+    pub fn exhausted(&self) -> impl Iterator<Item = (Capacity, u32)> + '_ {
+        self.exhausted.iter(Capacity::ALL)
+    }
+}
+
+/// Replays `trace` from `seed` under the ranked rules, like
+/// [`replay`](crate::replay), and explains the result.
+pub fn diagnose(seed: u64, trace: &[u8]) -> Diagnosis {
+    diagnose_sim(Sim::new(seed), trace)
+}
+
+/// Replays `trace` on `sim` (any config) and explains the result. Events
+/// already buffered in `sim` are discarded.
+pub fn diagnose_sim(mut sim: Sim, trace: &[u8]) -> Diagnosis {
+    let mut forms = FormLog::new();
+    let mut exhausted = FirstTicks::new();
+    sim.drain_events().for_each(drop);
+    for &input in trace.iter().take(MAX_TICKS as usize) {
+        if sim.over() {
+            break;
+        }
+        sim.tick(input);
+        for event in sim.drain_events() {
+            if let GameEvent::FormChanged { from, to } = event {
+                forms.push(FormChange {
+                    tick: sim.ticks(),
+                    from,
+                    to,
+                });
+            }
+        }
+        for c in sim.exhausted() {
+            exhausted.note(c as usize, sim.ticks());
+        }
+    }
+    let ending = if sim.form == DarioState::GameOver {
+        killer(&sim).map_or(Ending::Unfinished, Ending::Died)
+    } else if sim.over() || sim.ticks() >= MAX_TICKS {
+        Ending::TimeUp
+    } else {
+        Ending::Unfinished
+    };
+    Diagnosis {
+        score: sim.score(),
+        ticks: sim.ticks(),
+        over: sim.over(),
+        ending,
+        breakdown: sim.score_breakdown(),
+        forms,
+        exhausted,
+    }
+}
+
+/// The fatal collision. The tick returns right after it, so the killer is
+/// the first overlap in the sim's own check order that is still active.
+fn killer(sim: &Sim) -> Option<Death> {
+    let player = Hitbox::from(sim.player_hitbox());
+    let death = |killer, hitbox| Death {
+        tick: sim.ticks(),
+        killer,
+        player,
+        hitbox,
+    };
+    let obstacle = (0..MAX_OBSTACLES).find_map(|slot| {
+        let o = &sim.obstacles[slot];
+        let hitbox = Hitbox::from(Sim::obstacle_hitbox(o));
+        (o.active && o.kind != KIND_PLATFORM && player.overlaps(&hitbox)).then_some(death(
+            Killer::Obstacle {
+                slot,
+                id: o.id,
+                kind: o.kind,
+            },
+            hitbox,
+        ))
+    });
+    obstacle.or_else(|| {
+        (0..MAX_BOSS_SHOTS).find_map(|slot| {
+            let s = &sim.boss_shots[slot];
+            let hitbox = Hitbox {
+                x: s.x,
+                y: s.y,
+                w: BOSS_SHOT_SIZE * FP,
+                h: BOSS_SHOT_SIZE * FP,
+            };
+            (s.active && player.overlaps(&hitbox))
+                .then_some(death(Killer::BossShot { slot, id: s.id }, hitbox))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{replay, KIND_BARREL, KIND_BAT, KIND_PIPE};
+
+    #[test]
+    fn idle_deaths_name_the_obstacle_and_add_up() {
+        for seed in [1, 42, 99] {
+            let trace = [0u8; 2000];
+            let d = diagnose(seed, &trace);
+            let result = replay(seed, &trace);
+            assert_eq!(
+                (d.score, d.ticks, d.over),
+                (result.score, result.ticks, result.over)
+            );
+            assert_eq!(d.breakdown.total(), d.score);
+            let Ending::Died(death) = d.ending else {
+                panic!("seed {seed}: idle run should die");
+            };
+            assert_eq!(death.tick, d.ticks);
+            assert!(death.player.overlaps(&death.hitbox));
+            let Killer::Obstacle { kind, .. } = death.killer else {
+                panic!("no boss shots in ranked runs");
+            };
+            assert!([KIND_BARREL, KIND_PIPE, KIND_BAT].contains(&kind));
+            let last = d.forms().last().unwrap();
+            assert_eq!((last.tick, last.to), (death.tick, DarioState::GameOver));
+            assert_eq!(d.exhausted().count(), 0);
+        }
+    }
+
+    #[test]
+    fn short_traces_are_unfinished() {
+        let d = diagnose(7, &[0; 30]);
+        assert_eq!(d.ending, Ending::Unfinished);
+        assert!(d.forms().is_empty());
+        assert_eq!(d.breakdown.total(), d.score);
+    }
+}
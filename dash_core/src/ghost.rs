@@ -0,0 +1,190 @@
+//! Ghost races: a live run alongside replayed runs on the same seed.
+//!
+//! A [`GhostRace`] ticks the live [`Sim`] and up to [`MAX_GHOSTS`] ghosts
+//! in lockstep, each ghost replaying one stored input per tick, so every
+//! ghost is exactly where its recorded run was at the same tick. Ghosts
+//! never touch the live run. Traces are anything `AsRef<[u8]>`: borrowed
+//! slices natively, owned buffers in `dash_web`.
+
+use crate::{view, Sim, FP, PLAYER_H, PLAYER_W, PLAYER_X};
+use dario_fsm::DarioState;
+
+/// Ghost slots per race.
+pub const MAX_GHOSTS: usize = 8;
+
+/// A replayed run.
+pub struct Ghost<T> {
+    sim: Sim,
+    trace: T,
+}
+
+impl<T: AsRef<[u8]>> Ghost<T> {
+    fn step(&mut self) {
+        let next = self.trace.as_ref().get(self.sim.ticks() as usize);
+        if let (Some(&input), false) = (next, self.sim.over()) {
+            self.sim.tick(input);
+        }
+    }
+
+    pub fn sim(&self) -> &Sim {
+        &self.sim
+    }
+
+    pub fn alive(&self) -> bool {
+        self.sim.form != DarioState::GameOver
+    }
+
+    /// Whether the ghost has stopped: it died, ran out of time or reached
+    /// the end of its trace.
+    pub fn finished(&self) -> bool {
+        self.sim.over() || self.sim.ticks() as usize >= self.trace.as_ref().len()
+    }
+}
+
+/// A live run racing replayed traces on one seed, under the ranked rules.
+pub struct GhostRace<T> {
+    seed: u64,
+    live: Sim,
+    ghosts: [Option<Ghost<T>>; MAX_GHOSTS],
+}
+
+impl<T: AsRef<[u8]>> GhostRace<T> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            live: Sim::new(seed),
+            ghosts: core::array::from_fn(|_| None),
+        }
+    }
+
+    /// Adds a ghost replaying `trace` (recorded with `Sim::new` on this
+    /// race's seed), caught up to the live run's tick. Returns its slot, or
+    /// `None` when all [`MAX_GHOSTS`] are taken.
+    pub fn add_ghost(&mut self, trace: T) -> Option<usize> {
+        let slot = self.ghosts.iter().position(Option::is_none)?;
+        let mut ghost = Ghost {
+            sim: Sim::new(self.seed),
+            trace,
+        };
+        while ghost.sim.ticks() < self.live.ticks() && !ghost.finished() {
+            ghost.step();
+        }
+        self.ghosts[slot] = Some(ghost);
+        Some(slot)
+    }
+
+    pub fn remove_ghost(&mut self, slot: usize) -> Option<Ghost<T>> {
+        self.ghosts.get_mut(slot)?.take()
+    }
+
+    /// Advances the live run by `input` and every ghost by its next stored
+    /// input. Ghosts keep running after the live run ends, so a race can be
+    /// watched to the finish.
+    pub fn tick(&mut self, input: u8) {
+        self.live.tick(input);
+        for ghost in self.ghosts.iter_mut().flatten() {
+            ghost.step();
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn live(&self) -> &Sim {
+        &self.live
+    }
+
+    /// The live run, for draining its events.
+    pub fn live_mut(&mut self) -> &mut Sim {
+        &mut self.live
+    }
+
+    pub fn ghost(&self, slot: usize) -> Option<&Ghost<T>> {
+        self.ghosts.get(slot)?.as_ref()
+    }
+
+    /// Every ghost relative to the live run, in slot order.
+    pub fn ghost_views(&self) -> impl Iterator<Item = view::Ghost> + '_ {
+        self.ghosts.iter().enumerate().filter_map(|(slot, ghost)| {
+            let ghost = ghost.as_ref()?;
+            let ahead = (ghost.sim.distance - self.live.distance) / i64::from(FP);
+            Some(view::Ghost {
+                id: slot as u32,
+                x: PLAYER_X + ahead.clamp(i32::MIN.into(), i32::MAX.into()) as i32,
+                y: ghost.sim.player_y / FP,
+                w: PLAYER_W,
+                h: PLAYER_H,
+                form: ghost.sim.form(),
+                alive: ghost.alive(),
+            })
+        })
+    }
+
+    /// The live run's place by distance: 1 plus the ghosts ahead of it.
+    pub fn place(&self) -> usize {
+        1 + self
+            .ghosts
+            .iter()
+            .flatten()
+            .filter(|g| g.sim.distance > self.live.distance)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{replay, INPUT_JUMP};
+
+    /// A jump every `every` ticks; outlives idling on the seeds used here.
+    fn hopping(every: usize) -> [u8; 4000] {
+        let mut trace = [0u8; 4000];
+        for input in trace.iter_mut().step_by(every) {
+            *input = INPUT_JUMP;
+        }
+        trace
+    }
+
+    #[test]
+    fn ghosts_replay_their_traces_in_lockstep() {
+        let idle = [0u8; 4000];
+        let hops = hopping(35);
+        let mut race = GhostRace::new(5);
+        assert_eq!(race.add_ghost(&idle[..]), Some(0));
+        assert_eq!(race.add_ghost(&hops[..]), Some(1));
+        for &input in &hops[..] {
+            race.tick(input);
+        }
+        // The live run copied the hopping ghost, tick for tick.
+        let hopper = race.ghost(1).unwrap();
+        assert_eq!(hopper.sim().player_px(), race.live().player_px());
+        assert!(hopper.sim().entities().eq(race.live().entities()));
+        assert_eq!(hopper.sim().score(), replay(5, &hops).score);
+        let views: [view::Ghost; 2] = core::array::from_fn(|i| race.ghost_views().nth(i).unwrap());
+        assert_eq!(views[1].x, PLAYER_X);
+        // The idle ghost died early and was left behind.
+        let idler = race.ghost(0).unwrap();
+        assert!(!idler.alive() && idler.finished());
+        assert_eq!(idler.sim().score(), replay(5, &idle).score);
+        assert!(!views[0].alive && views[0].x < PLAYER_X);
+        assert_eq!(race.place(), 1);
+    }
+
+    #[test]
+    fn late_ghosts_catch_up_and_slots_run_out() {
+        let idle = [0u8; 4000];
+        let mut race = GhostRace::new(9);
+        for _ in 0..30 {
+            race.tick(0);
+        }
+        let slot = race.add_ghost(&idle[..]).unwrap();
+        assert_eq!(race.ghost(slot).unwrap().sim().ticks(), 30);
+        for _ in 1..MAX_GHOSTS {
+            assert!(race.add_ghost(&idle[..]).is_some());
+        }
+        assert_eq!(race.add_ghost(&idle[..]), None);
+        assert!(race.remove_ghost(slot).is_some());
+        assert_eq!(race.add_ghost(&idle[..]), Some(slot));
+    }
+}
@@ -12,7 +12,20 @@
 //! All randomness comes from a seeded xorshift64* PRNG.
 #![no_std]
 
-use dario_fsm::{transition, DarioState, Event};
+use dario_fsm::{is_powered, step, transition, DarioState, Event, Hearts, Loadout, PowerUp};
+use dario_levels::{Chunker, Piece};
+
+mod config;
+pub mod desync;
+pub mod diagnose;
+pub mod ghost;
+pub mod rollback;
+mod save;
+pub mod versus;
+pub mod view;
+pub use config::{ConfigError, SimConfig};
+pub use dario_levels::Biome;
+pub use save::{SaveError, SAVE_CAP, SAVE_MAGIC, SAVE_VERSION};
 
 /// Simulation tick rate in Hz.
 pub const TICK_HZ: u32 = 60;
@@ -28,22 +41,17 @@ pub const GROUND_Y: i32 = 464;
 /// Input bitflags, one byte per tick.
 pub const INPUT_JUMP: u8 = 1;
 pub const INPUT_FIRE: u8 = 2;
+/// Deploys the reserve item (only with [`Sim::with_reserve`]).
+pub const INPUT_USE: u8 = 4;
 /// Only these bits are meaningful; traces must not set others.
-pub const INPUT_MASK: u8 = INPUT_JUMP | INPUT_FIRE;
+pub const INPUT_MASK: u8 = INPUT_JUMP | INPUT_FIRE | INPUT_USE;
 
 /// Hard cap on run length (10 minutes) so proofs stay bounded.
 pub const MAX_TICKS: u32 = TICK_HZ * 600;
 
-// Physics constants, converted from px/s (px/s²) to fp/tick (fp/tick²).
-const GRAVITY: i32 = 185; // 2600 px/s²
-const JUMP_V: i32 = -3925; // -920 px/s
-const SUPER_JUMP_V: i32 = -4608; // -1080 px/s
-const GLIDE_FALL_CAP: i32 = 640; // 150 px/s
-const BASE_SPEED: i32 = 1408; // 330 px/s
-const MAX_SPEED: i32 = 3243; // 760 px/s
+// Fixed physics, converted from px/s to fp/tick. Tunable values live in
+// `SimConfig`.
 const FIREBALL_SPEED: i32 = 2645; // 620 px/s
-const FIREBALL_COOLDOWN: u32 = 27; // 0.45 s
-const INVULN_TICKS: u32 = 78; // 1.3 s
 
 const PLAYER_X: i32 = 130;
 const PLAYER_W: i32 = 46;
@@ -52,37 +60,90 @@ const PLAYER_H: i32 = 74;
 pub const MAX_OBSTACLES: usize = 16;
 pub const MAX_ITEMS: usize = 8;
 pub const MAX_FIREBALLS: usize = 4;
+/// Coin slots; only used with [`SimConfig::coins`].
+pub const MAX_COINS: usize = 24;
+
+/// A fixed slot table. When one is full the spawn or shot that needed a
+/// slot is skipped without a trace; [`Sim::exhausted`] reports which
+/// tables ran out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capacity {
+    /// [`MAX_OBSTACLES`]: an obstacle or chunk piece was not spawned.
+    Obstacles,
+    /// [`MAX_ITEMS`]: an item was not spawned.
+    Items,
+    /// [`MAX_FIREBALLS`]: a fire press with the cooldown over shot nothing.
+    Fireballs,
+    /// [`MAX_COINS`]: a coin arc was cut short.
+    Coins,
+    /// [`MAX_BOSS_SHOTS`]: a boss throw was skipped.
+    BossShots,
+}
+
+impl Capacity {
+    pub const ALL: [Capacity; 5] = [
+        Capacity::Obstacles,
+        Capacity::Items,
+        Capacity::Fireballs,
+        Capacity::Coins,
+        Capacity::BossShots,
+    ];
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
 
 /// Entity kinds exposed in snapshots.
 pub const KIND_BARREL: i32 = 0;
 pub const KIND_PIPE: i32 = 1;
 pub const KIND_BAT: i32 = 2;
+/// Hangs from the top of the world, low enough to catch super jumps only.
+/// Only spawned with [`SimConfig::hazards`].
+pub const KIND_SPIKE: i32 = 3;
+/// Bobbing platform Dario can land on; never hurts. Only spawned with
+/// [`SimConfig::hazards`].
+pub const KIND_PLATFORM: i32 = 4;
+
+/// Platform bob: triangle wave, period 120 ticks, ±16 px. Slower than
+/// gravity so a standing rider never drops off a descending platform.
+const PLATFORM_PERIOD: u32 = 120;
+const PLATFORM_AMP: i32 = 16;
+/// How far above a platform's top Dario's feet may start the tick and
+/// still land, so riders follow a rising platform.
+const PLATFORM_SNAP: i32 = 4 * FP;
 
 /// Item kinds (map 1:1 to FSM events).
 pub const ITEM_ESPRESSO: i32 = 0;
 pub const ITEM_CHILI: i32 = 1;
 pub const ITEM_CAPE: i32 = 2;
+/// Restores one heart; only spawned with [`Sim::with_hearts`].
+pub const ITEM_HEART: i32 = 3;
 
 #[derive(Clone, Copy, Default)]
 struct Obstacle {
     active: bool,
+    /// Stable render id, see [`view`].
+    id: u32,
     kind: i32,
     /// Left edge, fp.
     x: i32,
-    /// Barrels/pipes: ground anchor (bottom). Bats: top edge, fp.
+    /// Barrels/pipes/spikes: ground anchor (bottom). Bats/platforms: top
+    /// edge, fp.
     y: i32,
     /// Pixels.
     w: i32,
     h: i32,
-    /// Bats: base y (fp) for the triangle-wave hover.
+    /// Bats/platforms: base y (fp) for the triangle-wave hover.
     base_y: i32,
-    /// Bats: hover phase in ticks.
+    /// Bats/platforms: hover phase in ticks.
     phase: u32,
 }
 
 #[derive(Clone, Copy, Default)]
 struct Item {
     active: bool,
+    id: u32,
     kind: i32,
     x: i32,
     y: i32,
@@ -93,12 +154,86 @@ const ITEM_SIZE: i32 = 34;
 #[derive(Clone, Copy, Default)]
 struct Fireball {
     active: bool,
+    id: u32,
     x: i32,
     y: i32,
 }
 
 const FIREBALL_SIZE: i32 = 18;
 
+#[derive(Clone, Copy, Default)]
+struct Coin {
+    active: bool,
+    id: u32,
+    x: i32,
+    y: i32,
+}
+
+/// Points per coin.
+pub const COIN_SCORE: u64 = 10;
+const COIN_SIZE: i32 = 20;
+/// Coin heights above the ground line (px), one per coin of an arc: the
+/// ends are grabbed running, the top only by a jump timed to the arc.
+const COIN_ARC: [i32; 5] = [44, 96, 120, 96, 44];
+const COIN_SPACING: i32 = 44;
+
+/// Scrolled distance of the first boss, px; later ones follow every
+/// [`BOSS_EVERY_PX`], skipping thresholds passed during a fight.
+pub const BOSS_FIRST_PX: u32 = 6000;
+pub const BOSS_EVERY_PX: u32 = 20000;
+/// Points per fireball hit on a boss, and per boss defeated.
+pub const BOSS_HIT_SCORE: u64 = 20;
+pub const BOSS_KILL_SCORE: u64 = 250;
+pub const MAX_BOSS_SHOTS: usize = 6;
+const BOSS_W: i32 = 96;
+const BOSS_H: i32 = 120;
+/// Resting left edge, px.
+const BOSS_X: i32 = 760;
+/// Hover center of the top edge, px; low enough that fireballs shot from
+/// the ground reach the boss near the bottom of its hover.
+const BOSS_HOVER_Y: i32 = GROUND_Y - 150;
+const BOSS_HOVER_AMP: i32 = 24;
+const BOSS_HOVER_PERIOD: u32 = 150;
+/// Speed entering and leaving the screen, fp/tick.
+const BOSS_SLIDE: i32 = 4 * FP;
+/// Ticks a boss fights before leaving undefeated.
+const BOSS_STAY: u32 = 20 * TICK_HZ;
+const BOSS_THROW_GAP: u32 = 75;
+/// Shot bottom edges above the ground line (px), cycled by throw: low
+/// shots must be jumped, high ones pass over Dario on the ground.
+const BOSS_VOLLEY: [i32; 8] = [16, 16, 76, 16, 76, 76, 16, 76];
+const BOSS_SHOT_SIZE: i32 = 24;
+/// Shots fly in screen space, fp/tick.
+const BOSS_SHOT_SPEED: i32 = 7 * FP;
+
+/// Fireball hits the `index`th boss of a run takes.
+fn boss_hp(index: u32) -> u8 {
+    (4 + 2 * index).min(12) as u8
+}
+
+#[derive(Clone, Copy, Default)]
+struct Boss {
+    active: bool,
+    id: u32,
+    /// Top-left corner, fp.
+    x: i32,
+    y: i32,
+    hp: u8,
+    max_hp: u8,
+    /// Ticks since it appeared.
+    ticks: u32,
+    /// Shots thrown so far, indexing [`BOSS_VOLLEY`].
+    thrown: u32,
+}
+
+#[derive(Clone, Copy, Default)]
+struct BossShot {
+    active: bool,
+    id: u32,
+    x: i32,
+    y: i32,
+}
+
 /// xorshift64* PRNG.
 #[derive(Clone, Copy)]
 struct Rng(u64);
@@ -136,11 +271,21 @@ fn triangle(phase: u32, period: u32, amp: i32) -> i32 {
 /// The full deterministic game state.
 #[derive(Clone)]
 pub struct Sim {
+    seed: u64,
     rng: Rng,
     ticks: u32,
     over: bool,
     form: DarioState,
-    score: u64,
+    /// Ticks spent in the current form.
+    form_ticks: u32,
+    /// Physics, difficulty and rule variants; fixed for the run.
+    config: SimConfig,
+    /// One-slot reserve item; only used when `config.reserve` is set.
+    reserve: Option<PowerUp>,
+    /// Hearts-mode health; `None` keeps the classic one-hit rules.
+    hearts: Option<Hearts>,
+    /// The score by source, summed at the start of each tick.
+    score: ScoreBreakdown,
     pickups: u32,
     kills: u32,
     /// Scrolled distance, fp.
@@ -154,9 +299,98 @@ pub struct Sim {
     prev_input: u8,
     spawn_in: u32,
     item_in: u32,
+    /// Coins collected and ticks to the next arc; only used when
+    /// `config.coins` is set.
+    coins: u32,
+    coin_in: u32,
+    /// Chunk sequence; only used when `config.chunks` is set.
+    chunker: Chunker,
+    /// Boss state; only used when `config.bosses` is set. `bosses` counts
+    /// the bosses met and `boss_at` is the next threshold, px.
+    boss: Boss,
+    boss_shots: [BossShot; MAX_BOSS_SHOTS],
+    bosses: u32,
+    boss_at: u32,
+    boss_hits: u32,
+    bosses_defeated: u32,
     obstacles: [Obstacle; MAX_OBSTACLES],
     items: [Item; MAX_ITEMS],
     fireballs: [Fireball; MAX_FIREBALLS],
+    coin_slots: [Coin; MAX_COINS],
+    /// Next entity id to hand out; ids never repeat within a run.
+    next_id: u32,
+    /// Rolling hash of the state after every tick once `hashing` is on,
+    /// for desync detection.
+    state_hash: u64,
+    hashing: bool,
+    /// Events since the last drain; output only, not part of the state.
+    events: [GameEvent; MAX_EVENTS],
+    event_count: usize,
+    /// [`Capacity`] bits that ran out this run; output only, like `events`.
+    exhausted: u8,
+}
+
+/// Capacity of the event buffer between drains.
+pub const MAX_EVENTS: usize = 64;
+
+/// Something that happened during a tick, for sounds and effects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    JumpStarted,
+    Landed,
+    /// An item (`ITEM_*`) was collected.
+    ItemPicked {
+        kind: i32,
+    },
+    FireballShot,
+    /// A coin was collected (coin mode only).
+    CoinPicked,
+    /// A boss arrived with `hp` hit points (boss mode only).
+    BossAppeared {
+        hp: u8,
+    },
+    /// A fireball hit the boss, leaving it `hp` hit points.
+    BossHit {
+        hp: u8,
+    },
+    BossDefeated,
+    /// The boss left undefeated.
+    BossEscaped,
+    /// A fireball destroyed an obstacle (`KIND_*`).
+    ObstacleKilled {
+        kind: i32,
+    },
+    /// Dario took a hit (not while invulnerable).
+    Damaged,
+    FormChanged {
+        from: DarioState,
+        to: DarioState,
+    },
+    GameOver,
+}
+
+/// A score by source; [`ScoreBreakdown::total`] is the score.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    /// 0.02 points per scrolled pixel.
+    pub distance: u64,
+    /// 50 per power-up item; hearts do not score.
+    pub pickups: u64,
+    /// 25 per fireball kill.
+    pub kills: u64,
+    /// [`COIN_SCORE`] per coin, coin mode only.
+    pub coins: u64,
+    /// Boss hits and defeats, boss mode only.
+    pub bosses: u64,
+    /// Points a score cap removed. `dash_core` scores are uncapped, so
+    /// always 0; kept so both sims' breakdowns read alike.
+    pub capped_by: u64,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> u64 {
+        self.distance + self.pickups + self.kills + self.coins + self.bosses - self.capped_by
+    }
 }
 
 /// Result of replaying a full input trace.
@@ -180,12 +414,30 @@ fn overlap(a: &Aabb, b: &Aabb) -> bool {
 
 impl Sim {
     pub fn new(seed: u64) -> Self {
+        Self::build(seed, SimConfig::NORMAL)
+    }
+
+    /// A run under `config`, or why it cannot play (see
+    /// [`SimConfig::validate`]). Anything but [`SimConfig::NORMAL`] is
+    /// unranked: the RISC Zero guest always replays with [`Sim::new`].
+    pub fn with_config(seed: u64, config: SimConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(Self::build(seed, config))
+    }
+
+    /// A run under a config known to be valid.
+    fn build(seed: u64, config: SimConfig) -> Self {
         Self {
+            seed,
             rng: Rng::new(seed),
             ticks: 0,
             over: false,
             form: DarioState::Regular,
-            score: 0,
+            form_ticks: 0,
+            config,
+            reserve: None,
+            hearts: config.hearts.map(Hearts::full),
+            score: ScoreBreakdown::default(),
             pickups: 0,
             kills: 0,
             distance: 0,
@@ -197,34 +449,193 @@ impl Sim {
             prev_input: 0,
             spawn_in: 84, // 1.4 s
             item_in: 96,  // 1.6 s
+            coins: 0,
+            coin_in: 150, // 2.5 s
+            chunker: Chunker::new(seed),
+            boss: Boss::default(),
+            boss_shots: [BossShot::default(); MAX_BOSS_SHOTS],
+            bosses: 0,
+            boss_at: BOSS_FIRST_PX,
+            boss_hits: 0,
+            bosses_defeated: 0,
             obstacles: [Obstacle::default(); MAX_OBSTACLES],
             items: [Item::default(); MAX_ITEMS],
             fireballs: [Fireball::default(); MAX_FIREBALLS],
+            coin_slots: [Coin::default(); MAX_COINS],
+            next_id: 0,
+            state_hash: 0,
+            hashing: false,
+            events: [GameEvent::GameOver; MAX_EVENTS],
+            event_count: 0,
+            exhausted: 0,
         }
     }
 
+    /// Like [`Sim::new`], but powered forms revert to `Regular` after
+    /// `expire_ticks` ticks (`seconds * TICK_HZ`). Unranked: the RISC Zero
+    /// guest always replays with [`Sim::new`].
+    pub fn with_form_expiry(seed: u64, expire_ticks: u32) -> Self {
+        Self::build(
+            seed,
+            SimConfig {
+                form_expiry: Some(expire_ticks),
+                ..SimConfig::NORMAL
+            },
+        )
+    }
+
+    /// Like [`Sim::new`], but with a one-slot reserve: surplus power-ups are
+    /// stored instead of lost and [`INPUT_USE`] deploys them. Unranked.
+    pub fn with_reserve(seed: u64) -> Self {
+        Self::build(
+            seed,
+            SimConfig {
+                reserve: true,
+                ..SimConfig::NORMAL
+            },
+        )
+    }
+
+    /// Like [`Sim::new`], but in hearts mode: a hit taken as `Regular` costs
+    /// one of `max_hearts` hearts (at least one), the run ends at zero and heart pickups
+    /// ([`ITEM_HEART`]) join the item rolls. Unranked.
+    pub fn with_hearts(seed: u64, max_hearts: u8) -> Self {
+        Self::build(
+            seed,
+            SimConfig {
+                hearts: Some(max_hearts.max(1)),
+                ..SimConfig::NORMAL
+            },
+        )
+    }
+
+    /// Like [`Sim::new`], but ceiling spikes ([`KIND_SPIKE`]) and moving
+    /// platforms ([`KIND_PLATFORM`]) join the obstacle rolls. Unranked.
+    pub fn with_hazards(seed: u64) -> Self {
+        Self::build(
+            seed,
+            SimConfig {
+                hazards: true,
+                ..SimConfig::NORMAL
+            },
+        )
+    }
+
+    /// Like [`Sim::new`], but coin arcs spawn and every coin adds
+    /// [`COIN_SCORE`] points. Unranked.
+    pub fn with_coins(seed: u64) -> Self {
+        Self::build(
+            seed,
+            SimConfig {
+                coins: true,
+                ..SimConfig::NORMAL
+            },
+        )
+    }
+
+    /// Like [`Sim::new`], but obstacles come in hand-authored chunks
+    /// grouped into biomes, getting harder as the run goes on. Unranked.
+    pub fn with_chunks(seed: u64) -> Self {
+        Self::build(
+            seed,
+            SimConfig {
+                chunks: true,
+                ..SimConfig::NORMAL
+            },
+        )
+    }
+
+    /// Like [`Sim::new`], but a boss appears at fixed distances: obstacle
+    /// spawns pause while it throws shots on a fixed pattern, and it leaves
+    /// after 20 s unless fireballs wear its hit points down first.
+    /// Unranked.
+    pub fn with_bosses(seed: u64) -> Self {
+        Self::build(
+            seed,
+            SimConfig {
+                bosses: true,
+                ..SimConfig::NORMAL
+            },
+        )
+    }
+
     /// Current horizontal scroll speed, fp/tick.
     fn speed(&self) -> i32 {
-        let s = BASE_SPEED + ((self.ticks as i64 * 64) / 100) as i32;
-        if s > MAX_SPEED {
-            MAX_SPEED
+        let c = &self.config;
+        let s = c.base_speed + ((self.ticks as i64 * c.speed_ramp as i64) / 100) as i32;
+        if s > c.max_speed {
+            c.max_speed
         } else {
             s
         }
     }
 
     fn apply_event(&mut self, event: Event) {
-        self.form = transition(self.form, event);
+        self.set_form(transition(self.form, event));
+    }
+
+    fn pick_up(&mut self, item: PowerUp) {
+        if self.config.reserve {
+            self.set_loadout(self.loadout().pick_up(item));
+        } else {
+            self.apply_event(item.event());
+        }
+    }
+
+    fn loadout(&self) -> Loadout {
+        Loadout {
+            form: self.form,
+            reserve: self.reserve,
+        }
+    }
+
+    fn set_loadout(&mut self, loadout: Loadout) {
+        self.reserve = loadout.reserve;
+        self.set_form(loadout.form);
+    }
+
+    fn set_form(&mut self, form: DarioState) {
+        if form != self.form {
+            self.form_ticks = 0;
+            self.emit(GameEvent::FormChanged {
+                from: self.form,
+                to: form,
+            });
+        }
+        self.form = form;
+    }
+
+    /// Records an event; once the buffer is full, later events are dropped
+    /// until the next drain.
+    fn emit(&mut self, event: GameEvent) {
+        if let Some(slot) = self.events.get_mut(self.event_count) {
+            *slot = event;
+            self.event_count += 1;
+        }
+    }
+
+    fn exhaust(&mut self, capacity: Capacity) {
+        self.exhausted |= capacity.bit();
+    }
+
+    fn take_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
     }
 
     fn spawn_obstacle(&mut self) {
         let slot = match self.obstacles.iter().position(|o| !o.active) {
             Some(i) => i,
-            None => return,
+            None => return self.exhaust(Capacity::Obstacles),
         };
-        let roll = self.rng.below(100);
+        // Hazards widen the roll so the classic kinds keep their odds
+        // relative to each other.
+        let roll = self.rng.below(if self.config.hazards { 120 } else { 100 });
+        let id = self.take_id();
         let o = &mut self.obstacles[slot];
         o.active = true;
+        o.id = id;
         o.x = (WORLD_W + 40) * FP;
         o.phase = 0;
         if roll < 60 {
@@ -237,35 +648,162 @@ impl Sim {
             o.w = 46;
             o.h = 84 + self.rng.below(31) as i32;
             o.y = GROUND_Y * FP;
-        } else {
+        } else if roll < 100 {
             o.kind = KIND_BAT;
             o.w = 40;
             o.h = 32;
             o.base_y = (GROUND_Y - 90 - self.rng.below(71) as i32) * FP;
             o.y = o.base_y;
             o.phase = self.rng.below(72);
+        } else if roll < 110 {
+            // Bottom edge 186..=221 px: above the reach of a normal jump
+            // from the ground (hitbox top ≈ 241 px), below that of a super
+            // jump (≈ 181 px).
+            let bottom = 186 + self.rng.below(36) as i32;
+            o.kind = KIND_SPIKE;
+            o.w = 36;
+            o.h = bottom;
+            o.y = bottom * FP;
+        } else {
+            o.kind = KIND_PLATFORM;
+            o.w = 110;
+            o.h = 14;
+            o.base_y = (GROUND_Y - 110 - self.rng.below(41) as i32) * FP;
+            o.y = o.base_y;
+            o.phase = self.rng.below(PLATFORM_PERIOD);
         }
     }
 
+    /// Spawns a chunk piece off the right edge.
+    fn spawn_piece(&mut self, piece: Piece) {
+        let Some(slot) = self.obstacles.iter().position(|o| !o.active) else {
+            return self.exhaust(Capacity::Obstacles);
+        };
+        let mut o = Obstacle {
+            active: true,
+            id: self.take_id(),
+            x: (WORLD_W + 40) * FP,
+            y: GROUND_Y * FP,
+            ..Obstacle::default()
+        };
+        match piece {
+            Piece::Barrel { w, h } => {
+                o.kind = KIND_BARREL;
+                o.w = i32::from(w);
+                o.h = i32::from(h);
+            }
+            Piece::Pipe { h } => {
+                o.kind = KIND_PIPE;
+                o.w = 46;
+                o.h = i32::from(h);
+            }
+            Piece::Bat { lift, phase } => {
+                o.kind = KIND_BAT;
+                o.w = 40;
+                o.h = 32;
+                o.base_y = (GROUND_Y - i32::from(lift)) * FP;
+                o.y = o.base_y;
+                o.phase = u32::from(phase);
+            }
+        }
+        self.obstacles[slot] = o;
+    }
+
     fn spawn_item(&mut self) {
         let slot = match self.items.iter().position(|i| !i.active) {
             Some(i) => i,
-            None => return,
+            None => return self.exhaust(Capacity::Items),
         };
-        let kind = self.rng.below(3) as i32;
+        let kinds = if self.hearts.is_some() { 4 } else { 3 };
+        let kind = self.rng.below(kinds) as i32;
         let high = self.rng.below(2) == 1;
         let y = if high {
             GROUND_Y - 150 - self.rng.below(61) as i32
         } else {
             GROUND_Y - 46
         };
+        let id = self.take_id();
         let it = &mut self.items[slot];
         it.active = true;
+        it.id = id;
         it.kind = kind;
         it.x = (WORLD_W + 30) * FP;
         it.y = y * FP;
     }
 
+    /// Spawns one arc of coins off the right edge, as far as slots allow.
+    fn spawn_coin_arc(&mut self) {
+        for (i, h) in COIN_ARC.into_iter().enumerate() {
+            let Some(slot) = self.coin_slots.iter().position(|c| !c.active) else {
+                return self.exhaust(Capacity::Coins);
+            };
+            let id = self.take_id();
+            self.coin_slots[slot] = Coin {
+                active: true,
+                id,
+                x: (WORLD_W + 30 + i as i32 * COIN_SPACING) * FP,
+                y: (GROUND_Y - h - COIN_SIZE) * FP,
+            };
+        }
+    }
+
+    /// Brings in the next boss once its distance is reached, and moves,
+    /// hovers and fires the current one.
+    fn step_boss(&mut self) {
+        if !self.boss.active {
+            if self.distance_px() >= u64::from(self.boss_at) {
+                let hp = boss_hp(self.bosses);
+                self.bosses += 1;
+                self.boss = Boss {
+                    active: true,
+                    id: self.take_id(),
+                    x: (WORLD_W + 40) * FP,
+                    y: BOSS_HOVER_Y * FP,
+                    hp,
+                    max_hp: hp,
+                    ticks: 0,
+                    thrown: 0,
+                };
+                self.emit(GameEvent::BossAppeared { hp });
+            }
+            return;
+        }
+        let b = &mut self.boss;
+        b.ticks += 1;
+        b.y = (BOSS_HOVER_Y + triangle(b.ticks, BOSS_HOVER_PERIOD, BOSS_HOVER_AMP)) * FP;
+        if b.ticks > BOSS_STAY {
+            b.x += BOSS_SLIDE;
+            if b.x > (WORLD_W + 40) * FP {
+                self.end_boss();
+                self.emit(GameEvent::BossEscaped);
+            }
+        } else if b.x > BOSS_X * FP {
+            b.x = (b.x - BOSS_SLIDE).max(BOSS_X * FP);
+        } else if b.ticks.is_multiple_of(BOSS_THROW_GAP) {
+            let lift = BOSS_VOLLEY[b.thrown as usize % BOSS_VOLLEY.len()];
+            b.thrown += 1;
+            let x = b.x;
+            match self.boss_shots.iter().position(|s| !s.active) {
+                Some(slot) => {
+                    self.boss_shots[slot] = BossShot {
+                        active: true,
+                        id: self.take_id(),
+                        x: x - BOSS_SHOT_SIZE * FP,
+                        y: (GROUND_Y - lift - BOSS_SHOT_SIZE) * FP,
+                    };
+                }
+                None => self.exhaust(Capacity::BossShots),
+            }
+        }
+    }
+
+    fn end_boss(&mut self) {
+        self.boss.active = false;
+        while u64::from(self.boss_at) <= self.distance_px() {
+            self.boss_at += BOSS_EVERY_PX;
+        }
+    }
+
     fn player_hitbox(&self) -> Aabb {
         Aabb {
             x: (PLAYER_X + 8) * FP,
@@ -293,22 +831,155 @@ impl Sim {
         }
     }
 
+    /// Lands a falling Dario on a platform whose top his feet crossed this
+    /// tick, and drops him when he walks off one.
+    fn land_on_platforms(&mut self, feet_before: i32, grounded_before: bool) {
+        if self.player_y >= GROUND_Y * FP {
+            return;
+        }
+        self.grounded = false;
+        if self.player_vy < 0 {
+            return;
+        }
+        let hitbox = self.player_hitbox();
+        let landing = self.obstacles.iter().find(|o| {
+            o.active
+                && o.kind == KIND_PLATFORM
+                && hitbox.x < o.x + o.w * FP
+                && o.x < hitbox.x + hitbox.w
+                && feet_before <= o.y + PLATFORM_SNAP
+                && self.player_y >= o.y
+        });
+        if let Some(o) = landing {
+            self.player_y = o.y;
+            self.player_vy = 0;
+            self.grounded = true;
+            if !grounded_before {
+                self.emit(GameEvent::Landed);
+            }
+        }
+    }
+
     fn hit_player(&mut self) {
         if self.invuln > 0 {
             return;
         }
-        self.apply_event(Event::TakeDamage);
+        self.emit(GameEvent::Damaged);
+        match self.hearts {
+            Some(hearts) => {
+                let (form, hearts) = hearts.apply(self.form, Event::TakeDamage);
+                self.hearts = Some(hearts);
+                self.set_form(form);
+            }
+            None => self.apply_event(Event::TakeDamage),
+        }
         if self.form == DarioState::GameOver {
             self.over = true;
+            self.emit(GameEvent::GameOver);
         } else {
-            self.invuln = INVULN_TICKS;
+            self.invuln = self.config.invuln_ticks;
         }
     }
 
     /// Advance the simulation one tick with the given input byte.
     pub fn tick(&mut self, input: u8) {
+        let ticks = self.ticks;
+        self.advance(input);
+        if self.hashing && self.ticks != ticks {
+            self.state_hash = self.rolled_hash();
+        }
+    }
+
+    /// Folds the full post-tick state into the previous `state_hash`:
+    /// every counter, the RNGs, the player and every field of each active
+    /// entity slot, ids included.
+    fn rolled_hash(&self) -> u64 {
+        let flags = u64::from(self.over)
+            | u64::from(self.grounded) << 1
+            | u64::from(self.config.reserve) << 2
+            | u64::from(self.boss.active) << 3;
+        let hearts = self
+            .hearts
+            .map_or(0, |h| u64::from(h.left) << 8 | u64::from(h.max));
+        let mut h = self.state_hash;
+        for word in [
+            self.rng.0,
+            u64::from(self.ticks),
+            flags,
+            self.form as u64,
+            u64::from(self.form_ticks),
+            self.reserve.map_or(u64::MAX, |item| item as u64),
+            hearts,
+            self.score.total(),
+            u64::from(self.pickups) << 32 | u64::from(self.kills),
+            self.distance as u64,
+            u64::from(self.player_y as u32) << 32 | u64::from(self.player_vy as u32),
+            u64::from(self.invuln) << 32 | u64::from(self.fire_cd),
+            u64::from(self.spawn_in) << 32 | u64::from(self.item_in),
+            u64::from(self.prev_input),
+            u64::from(self.next_id),
+        ] {
+            h = desync::mix(h, word);
+        }
+        if self.config.chunks {
+            let c = self.chunker.state();
+            h = desync::mix(h, c.rng);
+            h = desync::mix(h, u64::from(c.chunk) << 32 | u64::from(c.start_px));
+            h = desync::mix(
+                h,
+                u64::from(c.biome) << 16 | u64::from(c.pattern) << 8 | u64::from(c.next_slot),
+            );
+        }
+        if self.config.coins {
+            h = desync::mix(h, u64::from(self.coins) << 32 | u64::from(self.coin_in));
+            for (i, c) in self.coin_slots.iter().enumerate().filter(|(_, c)| c.active) {
+                h = desync::mix(h, (i as u64 + 192) << 32 | u64::from(c.id));
+                h = desync::mix(h, u64::from(c.x as u32) << 32 | u64::from(c.y as u32));
+            }
+        }
+        if self.config.bosses {
+            let b = &self.boss;
+            h = desync::mix(h, u64::from(self.bosses) << 32 | u64::from(self.boss_at));
+            h = desync::mix(
+                h,
+                u64::from(self.boss_hits) << 32 | u64::from(self.bosses_defeated),
+            );
+            h = desync::mix(h, u64::from(b.x as u32) << 32 | u64::from(b.y as u32));
+            h = desync::mix(
+                h,
+                u64::from(b.hp) << 56 | u64::from(b.ticks) << 24 | u64::from(b.thrown),
+            );
+            h = desync::mix(h, u64::from(b.max_hp) << 32 | u64::from(b.id));
+            for (i, s) in self.boss_shots.iter().enumerate().filter(|(_, s)| s.active) {
+                h = desync::mix(h, (i as u64 + 224) << 32 | u64::from(s.id));
+                h = desync::mix(h, u64::from(s.x as u32) << 32 | u64::from(s.y as u32));
+            }
+        }
+        for (i, o) in self.obstacles.iter().enumerate().filter(|(_, o)| o.active) {
+            h = desync::mix(h, (i as u64) << 32 | u64::from(o.kind as u32));
+            h = desync::mix(h, u64::from(o.id) << 32 | u64::from(o.phase));
+            h = desync::mix(h, u64::from(o.x as u32) << 32 | u64::from(o.y as u32));
+            h = desync::mix(h, u64::from(o.w as u32) << 32 | u64::from(o.h as u32));
+            h = desync::mix(h, u64::from(o.base_y as u32));
+        }
+        for (i, it) in self.items.iter().enumerate().filter(|(_, it)| it.active) {
+            h = desync::mix(h, (i as u64 + 64) << 32 | u64::from(it.kind as u32));
+            h = desync::mix(h, u64::from(it.id));
+            h = desync::mix(h, u64::from(it.x as u32) << 32 | u64::from(it.y as u32));
+        }
+        for (i, f) in self.fireballs.iter().enumerate().filter(|(_, f)| f.active) {
+            h = desync::mix(h, (i as u64 + 128) << 32 | u64::from(f.id));
+            h = desync::mix(h, u64::from(f.x as u32) << 32 | u64::from(f.y as u32));
+        }
+        h
+    }
+
+    fn advance(&mut self, input: u8) {
         if self.over || self.ticks >= MAX_TICKS {
-            self.over = true;
+            if !self.over {
+                self.over = true;
+                self.emit(GameEvent::GameOver);
+            }
             return;
         }
         let input = input & INPUT_MASK;
@@ -319,9 +990,28 @@ impl Sim {
         let speed = self.speed();
         self.distance += i64::from(speed);
         // score: 0.02 points per scrolled pixel -> distance_fp / (256 * 50)
-        self.score = (self.distance / (i64::from(FP) * 50)) as u64
-            + u64::from(self.pickups) * 50
-            + u64::from(self.kills) * 25;
+        self.score = ScoreBreakdown {
+            distance: (self.distance / (i64::from(FP) * 50)) as u64,
+            pickups: u64::from(self.pickups) * 50,
+            kills: u64::from(self.kills) * 25,
+            coins: u64::from(self.coins) * COIN_SCORE,
+            bosses: u64::from(self.boss_hits) * BOSS_HIT_SCORE
+                + u64::from(self.bosses_defeated) * BOSS_KILL_SCORE,
+            capped_by: 0,
+        };
+
+        // Power-up expiry happens before this tick's physics and pickups.
+        self.form_ticks = self.form_ticks.saturating_add(1);
+        self.set_form(step(
+            self.form,
+            None,
+            self.form_ticks,
+            self.config.form_expiry,
+        ));
+
+        if pressed & INPUT_USE != 0 && self.config.reserve {
+            self.set_loadout(self.loadout().deploy());
+        }
 
         if self.invuln > 0 {
             self.invuln -= 1;
@@ -331,55 +1021,87 @@ impl Sim {
         }
 
         // Player physics
+        let feet_before = self.player_y;
+        let grounded_before = self.grounded;
         if pressed & INPUT_JUMP != 0 && self.grounded {
             self.player_vy = if self.form == DarioState::Super {
-                SUPER_JUMP_V
+                self.config.super_jump_v
             } else {
-                JUMP_V
+                self.config.jump_v
             };
             self.grounded = false;
+            self.emit(GameEvent::JumpStarted);
         }
-        self.player_vy += GRAVITY;
+        self.player_vy += self.config.gravity;
         if self.form == DarioState::Cape
             && input & INPUT_JUMP != 0
-            && self.player_vy > GLIDE_FALL_CAP
+            && self.player_vy > self.config.glide_fall_cap
         {
-            self.player_vy = GLIDE_FALL_CAP;
+            self.player_vy = self.config.glide_fall_cap;
         }
         self.player_y += self.player_vy;
         if self.player_y >= GROUND_Y * FP {
             self.player_y = GROUND_Y * FP;
             self.player_vy = 0;
+            if !self.grounded {
+                self.emit(GameEvent::Landed);
+            }
             self.grounded = true;
         }
 
         // Fireballs
         if pressed & INPUT_FIRE != 0 && self.form == DarioState::Fire && self.fire_cd == 0 {
             if let Some(slot) = self.fireballs.iter().position(|f| !f.active) {
-                self.fire_cd = FIREBALL_COOLDOWN;
+                self.fire_cd = self.config.fireball_cooldown;
                 self.fireballs[slot] = Fireball {
                     active: true,
+                    id: self.take_id(),
                     x: (PLAYER_X + PLAYER_W) * FP,
                     y: self.player_y - (PLAYER_H * 55 / 100) * FP,
                 };
+                self.emit(GameEvent::FireballShot);
+            } else {
+                self.exhaust(Capacity::Fireballs);
             }
         }
 
-        // Spawns
-        if self.spawn_in > 0 {
-            self.spawn_in -= 1;
-        }
-        if self.spawn_in == 0 {
-            self.spawn_obstacle();
-            let gap = 45 + self.rng.below(55);
-            self.spawn_in = (gap * BASE_SPEED as u32) / speed as u32 + 15;
+        // Spawns; obstacles pause during boss fights (the chunk clock runs
+        // on, dropping the pieces due meanwhile).
+        if self.config.chunks {
+            let clock = self.ticks * dario_levels::CLOCK_PX_PER_SEC / TICK_HZ;
+            while let Some(piece) = self.chunker.poll(clock) {
+                if !self.boss.active {
+                    self.spawn_piece(piece);
+                }
+            }
+        } else if !self.boss.active {
+            if self.spawn_in > 0 {
+                self.spawn_in -= 1;
+            }
+            if self.spawn_in == 0 {
+                self.spawn_obstacle();
+                let c = self.config;
+                let gap = c.gap_min + self.rng.below(c.gap_range);
+                self.spawn_in = (gap * c.base_speed as u32) / speed as u32 + c.gap_pad;
+            }
         }
         if self.item_in > 0 {
             self.item_in -= 1;
         }
         if self.item_in == 0 {
             self.spawn_item();
-            self.item_in = 132 + self.rng.below(193);
+            self.item_in = self.config.item_gap_min + self.rng.below(self.config.item_gap_range);
+        }
+        if self.config.coins {
+            self.coin_in -= 1;
+            if self.coin_in == 0 {
+                self.spawn_coin_arc();
+                self.coin_in = 120 + self.rng.below(120);
+            }
+        }
+
+        if self.config.bosses {
+            self.step_boss();
         }
 
         // Move world
@@ -388,6 +1110,9 @@ impl Sim {
             if o.kind == KIND_BAT {
                 o.phase += 1;
                 o.y = o.base_y + triangle(o.phase, 72, 20) * FP;
+            } else if o.kind == KIND_PLATFORM {
+                o.phase += 1;
+                o.y = o.base_y + triangle(o.phase, PLATFORM_PERIOD, PLATFORM_AMP) * FP;
             }
             if o.x + o.w * FP < -60 * FP {
                 o.active = false;
@@ -399,6 +1124,18 @@ impl Sim {
                 it.active = false;
             }
         }
+        for c in self.coin_slots.iter_mut().filter(|c| c.active) {
+            c.x -= speed;
+            if c.x + COIN_SIZE * FP < -40 * FP {
+                c.active = false;
+            }
+        }
+        for s in self.boss_shots.iter_mut().filter(|s| s.active) {
+            s.x -= BOSS_SHOT_SPEED;
+            if s.x + BOSS_SHOT_SIZE * FP < -40 * FP {
+                s.active = false;
+            }
+        }
         for f in self.fireballs.iter_mut().filter(|f| f.active) {
             f.x += FIREBALL_SPEED;
             if f.x > (WORLD_W + 40) * FP {
@@ -406,9 +1143,13 @@ impl Sim {
             }
         }
 
+        if self.config.hazards {
+            self.land_on_platforms(feet_before, grounded_before);
+        }
+
         // Item pickups
         let hitbox = self.player_hitbox();
-        let mut picked: [Option<Event>; MAX_ITEMS] = [None; MAX_ITEMS];
+        let mut picked: [Option<i32>; MAX_ITEMS] = [None; MAX_ITEMS];
         for (idx, it) in self.items.iter_mut().enumerate().filter(|(_, i)| i.active) {
             let box_ = Aabb {
                 x: it.x,
@@ -418,16 +1159,42 @@ impl Sim {
             };
             if overlap(&hitbox, &box_) {
                 it.active = false;
-                picked[idx] = Some(match it.kind {
-                    k if k == ITEM_CHILI => Event::ChiliPepper,
-                    k if k == ITEM_CAPE => Event::TableClothCape,
-                    _ => Event::Espresso,
-                });
+                picked[idx] = Some(it.kind);
             }
         }
-        for event in picked.into_iter().flatten() {
+        for kind in picked.into_iter().flatten() {
+            self.emit(GameEvent::ItemPicked { kind });
+            // A heart heals; it is no power-up, so it neither counts as a
+            // pickup nor scores.
+            if kind == ITEM_HEART {
+                self.hearts = self.hearts.map(Hearts::heal);
+                continue;
+            }
             self.pickups += 1;
-            self.apply_event(event);
+            match kind {
+                ITEM_CHILI => self.pick_up(PowerUp::ChiliPepper),
+                ITEM_CAPE => self.pick_up(PowerUp::TableClothCape),
+                _ => self.pick_up(PowerUp::Espresso),
+            }
+        }
+
+        // Coin pickups
+        let mut coins = 0;
+        for c in self.coin_slots.iter_mut().filter(|c| c.active) {
+            let box_ = Aabb {
+                x: c.x,
+                y: c.y,
+                w: COIN_SIZE * FP,
+                h: COIN_SIZE * FP,
+            };
+            if overlap(&hitbox, &box_) {
+                c.active = false;
+                coins += 1;
+            }
+        }
+        for _ in 0..coins {
+            self.coins += 1;
+            self.emit(GameEvent::CoinPicked);
         }
 
         // Fireball vs obstacle
@@ -442,21 +1209,55 @@ impl Sim {
                 h: FIREBALL_SIZE * FP,
             };
             for oi in 0..MAX_OBSTACLES {
-                if !self.obstacles[oi].active {
+                if !self.obstacles[oi].active || self.obstacles[oi].kind == KIND_PLATFORM {
                     continue;
                 }
                 if overlap(&fbox, &Self::obstacle_hitbox(&self.obstacles[oi])) {
                     self.obstacles[oi].active = false;
                     self.fireballs[fi].active = false;
                     self.kills += 1;
+                    self.emit(GameEvent::ObstacleKilled {
+                        kind: self.obstacles[oi].kind,
+                    });
                     break;
                 }
             }
         }
 
+        // Fireball vs boss
+        if self.boss.active {
+            let bbox = Aabb {
+                x: self.boss.x,
+                y: self.boss.y,
+                w: BOSS_W * FP,
+                h: BOSS_H * FP,
+            };
+            for fi in 0..MAX_FIREBALLS {
+                let f = self.fireballs[fi];
+                let fbox = Aabb {
+                    x: f.x,
+                    y: f.y,
+                    w: FIREBALL_SIZE * FP,
+                    h: FIREBALL_SIZE * FP,
+                };
+                if !f.active || !self.boss.active || !overlap(&fbox, &bbox) {
+                    continue;
+                }
+                self.fireballs[fi].active = false;
+                self.boss.hp -= 1;
+                self.boss_hits += 1;
+                self.emit(GameEvent::BossHit { hp: self.boss.hp });
+                if self.boss.hp == 0 {
+                    self.bosses_defeated += 1;
+                    self.end_boss();
+                    self.emit(GameEvent::BossDefeated);
+                }
+            }
+        }
+
         // Player vs obstacle
         for oi in 0..MAX_OBSTACLES {
-            if !self.obstacles[oi].active {
+            if !self.obstacles[oi].active || self.obstacles[oi].kind == KIND_PLATFORM {
                 continue;
             }
             if overlap(
@@ -470,6 +1271,24 @@ impl Sim {
                 self.obstacles[oi].active = false;
             }
         }
+
+        // Player vs boss shots
+        for si in 0..MAX_BOSS_SHOTS {
+            let s = self.boss_shots[si];
+            let sbox = Aabb {
+                x: s.x,
+                y: s.y,
+                w: BOSS_SHOT_SIZE * FP,
+                h: BOSS_SHOT_SIZE * FP,
+            };
+            if s.active && overlap(&self.player_hitbox(), &sbox) {
+                self.hit_player();
+                if self.over {
+                    return;
+                }
+                self.boss_shots[si].active = false;
+            }
+        }
     }
 
     // --- State accessors (for rendering and journals) ---
@@ -479,6 +1298,12 @@ impl Sim {
     }
 
     pub fn score(&self) -> u64 {
+        self.score.total()
+    }
+
+    /// Where [`Sim::score`] came from. Like the score, it is summed at the
+    /// start of a tick, so pickups and kills count from the next tick on.
+    pub fn score_breakdown(&self) -> ScoreBreakdown {
         self.score
     }
 
@@ -486,10 +1311,76 @@ impl Sim {
         self.ticks
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// True under [`SimConfig::NORMAL`], the rules the RISC Zero guest
+    /// replays.
+    pub fn ranked(&self) -> bool {
+        self.config.ranked()
+    }
+
+    pub fn config(&self) -> &SimConfig {
+        &self.config
+    }
+
+    /// Rolling hash over every post-tick state since
+    /// [`Sim::hash_states`]; 0 before. See [`desync`].
+    pub fn state_hash(&self) -> u64 {
+        self.state_hash
+    }
+
+    /// Turns on [`Sim::state_hash`] from the next tick. Off by default:
+    /// folding the whole state every tick is wasted work when replaying a
+    /// trace only for its outcome, as `replay` and the zkVM guest do.
+    pub fn hash_states(&mut self) {
+        self.hashing = true;
+    }
+
+    /// Events recorded since the last drain, oldest first.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events[..self.event_count]
+    }
+
+    /// Takes the recorded events, emptying the buffer. Drain at least every
+    /// few ticks; events past [`MAX_EVENTS`] are dropped.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = GameEvent> {
+        let n = core::mem::take(&mut self.event_count);
+        self.events.into_iter().take(n)
+    }
+
+    /// Slot tables that ran out at some point this run, each skipping a
+    /// spawn or shot. Not saved: a loaded sim starts with none.
+    pub fn exhausted(&self) -> impl Iterator<Item = Capacity> + '_ {
+        Capacity::ALL
+            .into_iter()
+            .filter(move |c| self.exhausted & c.bit() != 0)
+    }
+
     pub fn form(&self) -> u32 {
         self.form as u32
     }
 
+    /// Ticks until the current power-up expires, or `None` when forms do
+    /// not expire or Dario is not powered.
+    pub fn form_ticks_left(&self) -> Option<u32> {
+        match self.config.form_expiry {
+            Some(limit) if is_powered(self.form) => Some(limit.saturating_sub(self.form_ticks)),
+            _ => None,
+        }
+    }
+
+    /// Reserve item kind (`ITEM_*`), if one is stored.
+    pub fn reserve(&self) -> Option<u32> {
+        self.reserve.map(|item| item as u32)
+    }
+
+    /// Hearts left and maximum, in hearts mode.
+    pub fn hearts(&self) -> Option<(u8, u8)> {
+        self.hearts.map(|h| (h.left, h.max))
+    }
+
     pub fn pickups(&self) -> u32 {
         self.pickups
     }
@@ -498,6 +1389,52 @@ impl Sim {
         self.kills
     }
 
+    /// Current biome, with chunked levels.
+    pub fn biome(&self) -> Option<Biome> {
+        self.config.chunks.then(|| self.chunker.biome())
+    }
+
+    /// Coins collected (coin mode only).
+    pub fn coins_collected(&self) -> u32 {
+        self.coins
+    }
+
+    /// Fireball hits landed on bosses (boss mode only).
+    pub fn boss_hits(&self) -> u32 {
+        self.boss_hits
+    }
+
+    pub fn bosses_defeated(&self) -> u32 {
+        self.bosses_defeated
+    }
+
+    /// The boss on screen, if any.
+    pub fn boss(&self) -> Option<view::Boss> {
+        let b = &self.boss;
+        b.active.then_some(view::Boss {
+            id: b.id,
+            x: b.x / FP,
+            y: b.y / FP,
+            w: BOSS_W,
+            h: BOSS_H,
+            hp: b.hp,
+            max_hp: b.max_hp,
+        })
+    }
+
+    /// Active boss shots, in slot order.
+    pub fn boss_shots(&self) -> impl Iterator<Item = view::BossShot> + '_ {
+        self.boss_shots
+            .iter()
+            .filter(|s| s.active)
+            .map(|s| view::BossShot {
+                id: s.id,
+                x: s.x / FP,
+                y: s.y / FP,
+                size: BOSS_SHOT_SIZE,
+            })
+    }
+
     /// Distance scrolled, in whole pixels.
     pub fn distance_px(&self) -> u64 {
         (self.distance / i64::from(FP)) as u64
@@ -515,39 +1452,83 @@ impl Sim {
         self.grounded
     }
 
+    /// Active obstacles, in slot order.
+    pub fn obstacles(&self) -> impl Iterator<Item = view::Obstacle> + '_ {
+        self.obstacles
+            .iter()
+            .filter(|o| o.active)
+            .map(|o| view::Obstacle {
+                id: o.id,
+                kind: o.kind,
+                x: o.x / FP,
+                y: o.y / FP,
+                w: o.w,
+                h: o.h,
+            })
+    }
+
+    /// Active items, in slot order.
+    pub fn items(&self) -> impl Iterator<Item = view::Item> + '_ {
+        self.items.iter().filter(|i| i.active).map(|it| view::Item {
+            id: it.id,
+            kind: it.kind,
+            x: it.x / FP,
+            y: it.y / FP,
+            size: ITEM_SIZE,
+        })
+    }
+
+    /// Active fireballs, in slot order.
+    pub fn fireballs(&self) -> impl Iterator<Item = view::Fireball> + '_ {
+        self.fireballs
+            .iter()
+            .filter(|f| f.active)
+            .map(|f| view::Fireball {
+                id: f.id,
+                x: f.x / FP,
+                y: f.y / FP,
+                size: FIREBALL_SIZE,
+            })
+    }
+
+    /// Active coins, in slot order.
+    pub fn coins(&self) -> impl Iterator<Item = view::Coin> + '_ {
+        self.coin_slots
+            .iter()
+            .filter(|c| c.active)
+            .map(|c| view::Coin {
+                id: c.id,
+                x: c.x / FP,
+                y: c.y / FP,
+                size: COIN_SIZE,
+            })
+    }
+
+    /// Every active entity: obstacles, then items, then fireballs, then
+    /// coins, then the boss and its shots.
+    pub fn entities(&self) -> impl Iterator<Item = view::Entity> + '_ {
+        self.obstacles()
+            .map(view::Entity::Obstacle)
+            .chain(self.items().map(view::Entity::Item))
+            .chain(self.fireballs().map(view::Entity::Fireball))
+            .chain(self.coins().map(view::Entity::Coin))
+            .chain(self.boss().map(view::Entity::Boss))
+            .chain(self.boss_shots().map(view::Entity::BossShot))
+    }
+
     /// Writes a flat render snapshot into `out`, returning the number of
-    /// `i32` values written. Layout: repeated records of
-    /// `[entity_type, kind, x_px, y_px, w_px, h_px]` where entity_type is
-    /// 0 = obstacle (y = ground anchor for barrel/pipe, top edge for bat),
-    /// 1 = item, 2 = fireball.
+    /// `i32` values written: [`view::Entity::record`] per entity, in
+    /// [`Sim::entities`] order.
     pub fn snapshot(&self, out: &mut [i32]) -> usize {
-        let mut n = 0;
-        let mut push = |out: &mut [i32], rec: [i32; 6]| {
-            if n + 6 <= out.len() {
-                out[n..n + 6].copy_from_slice(&rec);
-                n += 6;
-            }
-        };
-        for o in self.obstacles.iter().filter(|o| o.active) {
-            push(out, [0, o.kind, o.x / FP, o.y / FP, o.w, o.h]);
-        }
-        for it in self.items.iter().filter(|i| i.active) {
-            push(
-                out,
-                [1, it.kind, it.x / FP, it.y / FP, ITEM_SIZE, ITEM_SIZE],
-            );
-        }
-        for f in self.fireballs.iter().filter(|f| f.active) {
-            push(
-                out,
-                [2, 0, f.x / FP, f.y / FP, FIREBALL_SIZE, FIREBALL_SIZE],
-            );
-        }
-        n
+        view::write_records(self.entities(), out)
     }
 
+    /// Maximum number of entities visible at once.
+    pub const MAX_ENTITIES: usize =
+        MAX_OBSTACLES + MAX_ITEMS + MAX_FIREBALLS + MAX_COINS + 1 + MAX_BOSS_SHOTS;
+
     /// Maximum `snapshot` output length in i32s.
-    pub const SNAPSHOT_CAP: usize = (MAX_OBSTACLES + MAX_ITEMS + MAX_FIREBALLS) * 6;
+    pub const SNAPSHOT_CAP: usize = Self::MAX_ENTITIES * view::RECORD_LEN;
 }
 
 /// Replays a full input trace (one byte per tick) from the given seed.
@@ -609,6 +1590,110 @@ mod tests {
             sim.tick(0);
         }
         assert!(sim.score() > 0 || sim.over());
+        let b = sim.score_breakdown();
+        assert_eq!(b.total(), sim.score());
+        assert_eq!((b.distance, b.capped_by), (sim.distance_px() / 50, 0));
+    }
+
+    #[test]
+    fn powered_forms_expire_back_to_regular() {
+        let mut sim = Sim::with_form_expiry(9, 120);
+        sim.apply_event(Event::ChiliPepper);
+        for _ in 0..119 {
+            sim.tick(0);
+        }
+        assert_eq!(sim.form(), DarioState::Fire as u32);
+        assert_eq!(sim.form_ticks_left(), Some(1));
+        sim.tick(0);
+        assert_eq!(sim.form(), DarioState::Regular as u32);
+        assert_eq!(sim.form_ticks_left(), None);
+
+        // Without expiry the same run keeps its form.
+        let mut classic = Sim::new(9);
+        classic.apply_event(Event::ChiliPepper);
+        for _ in 0..120 {
+            classic.tick(0);
+        }
+        assert_eq!(classic.form(), DarioState::Fire as u32);
+    }
+
+    #[test]
+    fn reserve_stores_surplus_power_ups_and_deploys_on_use() {
+        let mut sim = Sim::with_reserve(3);
+        sim.pick_up(PowerUp::ChiliPepper);
+        sim.pick_up(PowerUp::Espresso);
+        assert_eq!(sim.form(), DarioState::Fire as u32);
+        assert_eq!(sim.reserve(), Some(ITEM_ESPRESSO as u32));
+
+        sim.hit_player();
+        assert_eq!(sim.form(), DarioState::Regular as u32);
+        sim.tick(INPUT_USE);
+        assert_eq!(sim.form(), DarioState::Super as u32);
+        assert_eq!(sim.reserve(), None);
+
+        // Without a reserve the use bit is inert and surplus items are lost.
+        let mut classic = Sim::new(3);
+        classic.pick_up(PowerUp::ChiliPepper);
+        classic.pick_up(PowerUp::Espresso);
+        classic.tick(INPUT_USE);
+        assert_eq!(classic.form(), DarioState::Fire as u32);
+        assert_eq!(classic.reserve(), None);
+    }
+
+    #[test]
+    fn hearts_mode_ends_only_at_zero_hearts() {
+        let mut sim = Sim::with_hearts(4, 3);
+        sim.pick_up(PowerUp::Espresso);
+        sim.hit_player();
+        assert_eq!(sim.form(), DarioState::Regular as u32);
+        assert_eq!(sim.hearts(), Some((3, 3)));
+        for left in [2, 1] {
+            sim.invuln = 0;
+            sim.hit_player();
+            assert!(!sim.over());
+            assert_eq!(sim.hearts(), Some((left, 3)));
+        }
+        sim.hearts = sim.hearts.map(Hearts::heal);
+        assert_eq!(sim.hearts(), Some((2, 3)));
+        for _ in 0..2 {
+            sim.invuln = 0;
+            sim.hit_player();
+        }
+        assert!(sim.over());
+        assert_eq!(sim.form(), DarioState::GameOver as u32);
+    }
+
+    #[test]
+    fn hearts_heal_without_scoring() {
+        let mut hearts = 0;
+        for seed in 0..20 {
+            let mut sim = Sim::with_hearts(seed, 3);
+            let mut items = 0;
+            for i in 0..4000 {
+                sim.tick(if i % 45 < 10 { INPUT_JUMP } else { 0 });
+                for event in sim.drain_events() {
+                    match event {
+                        GameEvent::ItemPicked { kind: ITEM_HEART } => hearts += 1,
+                        GameEvent::ItemPicked { .. } => items += 1,
+                        _ => {}
+                    }
+                }
+            }
+            assert_eq!(sim.pickups(), items);
+            assert_eq!(sim.score_breakdown().pickups, u64::from(items) * 50);
+        }
+        assert!(hearts > 0);
+    }
+
+    #[test]
+    fn idle_hearts_run_dies_with_no_hearts_left() {
+        let result = replay(7, &[0u8; 30_000]);
+        let mut sim = Sim::with_hearts(7, 3);
+        while !sim.over() {
+            sim.tick(0);
+        }
+        assert_eq!(sim.hearts(), Some((0, 3)));
+        assert!(sim.ticks() > result.ticks);
     }
 
     #[test]
@@ -622,5 +1707,319 @@ mod tests {
             safety += 1;
         }
         assert!(sim.ticks() <= MAX_TICKS);
+        assert_eq!(dario_replay::SimKind::Core.max_ticks(), MAX_TICKS);
+    }
+
+    #[test]
+    fn events_mirror_counters_and_end_with_game_over() {
+        let mut sim = Sim::new(42);
+        sim.apply_event(Event::ChiliPepper);
+        let (mut jumps, mut landings, mut items, mut kills, mut over) = (0, 0, 0, 0, 0);
+        let mut i = 0u32;
+        while !sim.over() {
+            let mut input = if i % 50 < 12 { INPUT_JUMP } else { 0 };
+            if i.is_multiple_of(5) {
+                input |= INPUT_FIRE;
+            }
+            sim.tick(input);
+            i += 1;
+            for event in sim.drain_events() {
+                match event {
+                    GameEvent::JumpStarted => jumps += 1,
+                    GameEvent::Landed => landings += 1,
+                    GameEvent::ItemPicked { .. } => items += 1,
+                    GameEvent::ObstacleKilled { .. } => kills += 1,
+                    GameEvent::GameOver => over += 1,
+                    _ => {}
+                }
+            }
+            assert!(sim.events().is_empty());
+        }
+        assert!(jumps > 0 && landings + 1 >= jumps && landings <= jumps);
+        assert_eq!(items, sim.pickups());
+        assert_eq!(kills, sim.kills());
+        assert!(kills > 0);
+        assert_eq!(over, 1);
+    }
+
+    #[test]
+    fn entity_ids_track_entities_across_ticks() {
+        let mut sim = Sim::new(42);
+        let mut prev = [None; MAX_OBSTACLES];
+        let mut tracked = 0;
+        for i in 0..2000 {
+            sim.tick(if i % 50 < 12 { INPUT_JUMP } else { 0 });
+            if sim.over() {
+                break;
+            }
+            let mut ids = [0u32; MAX_OBSTACLES + MAX_ITEMS + MAX_FIREBALLS];
+            for (n, e) in sim.entities().enumerate() {
+                assert!(!ids[..n].contains(&e.id()), "duplicate id {}", e.id());
+                ids[n] = e.id();
+            }
+            for o in sim.obstacles() {
+                if let Some(p) = prev
+                    .iter()
+                    .flatten()
+                    .find(|p: &&view::Obstacle| p.id == o.id)
+                {
+                    assert_eq!((o.kind, o.w, o.h), (p.kind, p.w, p.h));
+                    assert!(o.x < p.x);
+                    tracked += 1;
+                }
+            }
+            prev = [None; MAX_OBSTACLES];
+            for (slot, o) in prev.iter_mut().zip(sim.obstacles()) {
+                *slot = Some(o);
+            }
+        }
+        assert!(tracked > 100);
+    }
+
+    #[test]
+    fn degenerate_configs_are_rejected() {
+        let cases = [
+            (
+                SimConfig {
+                    gap_range: 0,
+                    ..SimConfig::NORMAL
+                },
+                ConfigError::EmptyGapRange,
+            ),
+            (
+                SimConfig {
+                    item_gap_range: 0,
+                    ..SimConfig::NORMAL
+                },
+                ConfigError::EmptyGapRange,
+            ),
+            (
+                SimConfig {
+                    base_speed: 0,
+                    speed_ramp: 0,
+                    ..SimConfig::NORMAL
+                },
+                ConfigError::StalledSpeed,
+            ),
+            (
+                SimConfig {
+                    speed_ramp: -10,
+                    ..SimConfig::NORMAL
+                },
+                ConfigError::StalledSpeed,
+            ),
+            (
+                SimConfig {
+                    max_speed: -1,
+                    ..SimConfig::NORMAL
+                },
+                ConfigError::StalledSpeed,
+            ),
+            (
+                SimConfig {
+                    hearts: Some(0),
+                    ..SimConfig::NORMAL
+                },
+                ConfigError::NoHearts,
+            ),
+        ];
+        for (config, err) in cases {
+            assert_eq!(Sim::with_config(1, config).err(), Some(err));
+        }
+        assert_eq!(Sim::with_hearts(1, 0).config().hearts, Some(1));
+
+        // The narrowest valid config plays a whole run.
+        let mut sim = Sim::with_config(
+            3,
+            SimConfig {
+                gap_min: 0,
+                gap_range: 1,
+                item_gap_min: 0,
+                item_gap_range: 1,
+                speed_ramp: -1,
+                ..SimConfig::NORMAL
+            },
+        )
+        .unwrap();
+        for i in 0..MAX_TICKS {
+            sim.tick(if i % 30 < 8 { INPUT_JUMP } else { 0 });
+        }
+    }
+
+    #[test]
+    fn presets_are_distinct_and_only_normal_is_ranked() {
+        let mut trace = [0u8; 3000];
+        for (i, b) in trace.iter_mut().enumerate() {
+            if i % 45 < 10 {
+                *b = INPUT_JUMP;
+            }
+        }
+        let normal = replay(42, &trace);
+        let mut hashes = [0u64; 3];
+        for (i, config) in SimConfig::PRESETS.into_iter().enumerate() {
+            let mut sim = Sim::with_config(42, config).unwrap();
+            for &input in &trace {
+                sim.tick(input);
+            }
+            assert_eq!(sim.ranked(), config == SimConfig::NORMAL);
+            if sim.ranked() {
+                assert_eq!((sim.score(), sim.ticks()), (normal.score, normal.ticks));
+            }
+            hashes[i] = config.hash();
+            assert_eq!(SimConfig::preset_by_hash(hashes[i]), Some(config));
+        }
+        assert!(hashes[0] != hashes[1] && hashes[1] != hashes[2] && hashes[0] != hashes[2]);
+        assert!(!Sim::with_reserve(42).ranked());
+        assert_ne!(
+            Sim::with_hearts(42, 3).config().hash(),
+            SimConfig::NORMAL.hash()
+        );
+    }
+
+    #[test]
+    fn hazards_spawn_only_when_enabled_and_platforms_carry_dario() {
+        let trace = |i: u32| if i % 40 < 20 { INPUT_JUMP } else { 0 };
+        let mut classic = Sim::new(3);
+        for i in 0..3000 {
+            classic.tick(trace(i));
+            assert!(classic.obstacles().all(|o| o.kind <= KIND_BAT));
+        }
+        assert!(!Sim::with_hazards(3).ranked());
+
+        // Plenty of hearts so the runs live long enough to meet every kind.
+        let config = SimConfig {
+            hazards: true,
+            hearts: Some(200),
+            ..SimConfig::NORMAL
+        };
+        let (mut spikes, mut platforms, mut rode) = (false, false, false);
+        for seed in 0..4 {
+            let mut sim = Sim::with_config(seed, config).unwrap();
+            for i in 0..3000 {
+                sim.tick(trace(i));
+                spikes |= sim.obstacles().any(|o| o.kind == KIND_SPIKE);
+                platforms |= sim.obstacles().any(|o| o.kind == KIND_PLATFORM);
+                let (_, y, _, _) = sim.player_px();
+                rode |= sim.grounded() && y < GROUND_Y;
+            }
+        }
+        assert!(spikes && platforms && rode);
+    }
+
+    #[test]
+    fn chunked_runs_spawn_the_chunk_sequence() {
+        let config = SimConfig {
+            chunks: true,
+            hearts: Some(200),
+            ..SimConfig::NORMAL
+        };
+        let mut sim = Sim::with_config(7, config).unwrap();
+        assert!(!sim.ranked() && Sim::new(7).biome().is_none());
+        let mut chunker = Chunker::new(7);
+        let mut next_id = 0;
+        let mut spawned = 0;
+        for i in 0..4000 {
+            sim.tick(if i % 50 < 12 { INPUT_JUMP } else { 0 });
+            let clock = sim.ticks() * dario_levels::CLOCK_PX_PER_SEC / TICK_HZ;
+            while let Some(piece) = chunker.poll(clock) {
+                let kind = match piece {
+                    Piece::Barrel { .. } => KIND_BARREL,
+                    Piece::Pipe { .. } => KIND_PIPE,
+                    Piece::Bat { .. } => KIND_BAT,
+                };
+                let o = sim.obstacles().find(|o| o.id >= next_id).unwrap();
+                assert_eq!(o.kind, kind);
+                next_id = o.id + 1;
+                spawned += 1;
+            }
+            assert_eq!(sim.biome(), Some(chunker.biome()));
+        }
+        assert!(spawned > 20);
+    }
+
+    #[test]
+    fn coins_spawn_in_arcs_and_score_only_in_coin_mode() {
+        let trace = |i: u32| if i % 50 < 12 { INPUT_JUMP } else { 0 };
+        let mut classic = Sim::new(5);
+        let mut sim = Sim::with_coins(5);
+        assert!(!sim.ranked());
+        let mut picked = 0;
+        for i in 0..1200 {
+            classic.tick(trace(i));
+            sim.tick(trace(i));
+            assert_eq!(classic.coins().count(), 0);
+            assert!(sim.coins().count() <= MAX_COINS);
+            picked += sim
+                .drain_events()
+                .filter(|e| *e == GameEvent::CoinPicked)
+                .count();
+        }
+        assert!(picked > 0);
+        assert_eq!(sim.coins_collected() as usize, picked);
+        assert_eq!(classic.coins_collected(), 0);
+
+        let mut buf = [0i32; Sim::SNAPSHOT_CAP];
+        let n = sim.snapshot(&mut buf);
+        assert_eq!(
+            buf[..n].chunks(6).filter(|r| r[0] == 3).count(),
+            sim.coins().count()
+        );
+    }
+
+    #[test]
+    fn bosses_appear_at_fixed_distances_and_score_hits() {
+        let trace = |i: u32| {
+            let jump = if i % 50 < 12 { INPUT_JUMP } else { 0 };
+            jump | if i.is_multiple_of(30) { INPUT_FIRE } else { 0 }
+        };
+        let mut classic = Sim::new(9);
+        for i in 0..3000 {
+            classic.tick(trace(i));
+            assert!(classic.boss().is_none());
+        }
+        assert!(!Sim::with_bosses(9).ranked());
+
+        let config = SimConfig {
+            bosses: true,
+            hearts: Some(200),
+            ..SimConfig::NORMAL
+        };
+        let mut sim = Sim::with_config(9, config).unwrap();
+        let (mut appeared, mut hits, mut defeated, mut shots) = (0, 0, 0, false);
+        for i in 0..6000 {
+            // Keep Dario armed so the fight can be won.
+            sim.form = DarioState::Fire;
+            sim.tick(trace(i));
+            for event in sim.drain_events() {
+                match event {
+                    GameEvent::BossAppeared { hp } => {
+                        assert_eq!(hp, boss_hp(appeared));
+                        if appeared == 0 {
+                            assert!(sim.distance_px() >= u64::from(BOSS_FIRST_PX));
+                        }
+                        appeared += 1;
+                    }
+                    GameEvent::BossHit { .. } => hits += 1,
+                    GameEvent::BossDefeated => defeated += 1,
+                    _ => {}
+                }
+            }
+            if let Some(boss) = sim.boss() {
+                // Obstacle spawns pause for the fight.
+                assert!(sim.obstacles().all(|o| o.id < boss.id));
+                shots |= sim.boss_shots().count() > 0;
+            }
+        }
+        assert!(appeared >= 1 && shots && defeated >= 1);
+        assert_eq!(sim.boss_hits(), hits);
+        assert_eq!(sim.bosses_defeated(), defeated);
+        let extra = u64::from(hits) * BOSS_HIT_SCORE + u64::from(defeated) * BOSS_KILL_SCORE;
+        assert_eq!(
+            sim.score(),
+            sim.distance_px() / 50
+                + u64::from(sim.pickups()) * 50
+                + u64::from(sim.kills()) * 25
+                + extra
+        );
     }
 }
@@ -0,0 +1,203 @@
+//! Rollback netcode support: cheap state snapshots and input prediction.
+//!
+//! `Sim` holds no pointers or heap data, so a [`SimState`] is a plain
+//! copy of it and saving or restoring one is a memcpy, cheap enough to do
+//! every tick (unlike [`Sim::save_to`], which is versioned and meant for
+//! storage). [`RollbackSession`] builds GGPO-style rollback on top: it runs
+//! ahead on predicted input, keeps the state before every unconfirmed
+//! tick, and when a real input disagrees with the prediction it restores
+//! the state before that tick and re-simulates to the present.
+
+use crate::{GameEvent, Sim, MAX_EVENTS};
+
+/// A snapshot of a [`Sim`] from [`Sim::snapshot_state`]. Only valid in
+/// this build; use [`Sim::save_to`] to persist a run.
+#[derive(Clone)]
+pub struct SimState(Sim);
+
+impl Sim {
+    /// Snapshots the whole state, for [`Sim::restore_state`].
+    pub fn snapshot_state(&self) -> SimState {
+        SimState(self.clone())
+    }
+
+    /// Rewinds to `state`. Events not yet drained are dropped; the ticks
+    /// replayed after a rewind emit their own.
+    pub fn restore_state(&mut self, state: &SimState) {
+        self.clone_from(&state.0);
+        self.events = [GameEvent::GameOver; MAX_EVENTS];
+        self.event_count = 0;
+    }
+}
+
+/// Ticks a [`RollbackSession`] may run ahead of its confirmed input.
+pub const ROLLBACK_WINDOW: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RollbackError {
+    /// [`ROLLBACK_WINDOW`] ticks are unconfirmed; wait for input.
+    WindowFull,
+    /// Every simulated tick is already confirmed; the input is for a tick
+    /// not simulated yet.
+    NothingToConfirm,
+}
+
+/// A sim driven by one remote input stream, predicted until confirmed.
+///
+/// Each tick runs on the latest confirmed input (or 0 before any), the
+/// usual prediction for a held button. [`RollbackSession::confirm`] takes
+/// the real inputs in tick order and rolls back on the first mismatch.
+pub struct RollbackSession {
+    sim: Sim,
+    /// Ticks advanced, including ticks after the run ended.
+    ticks: u32,
+    /// Ticks whose input is confirmed; always the oldest ones.
+    confirmed: u32,
+    /// For tick `t` in `confirmed..ticks`, the state before it and the
+    /// input it ran on, at index `t % ROLLBACK_WINDOW`.
+    states: [SimState; ROLLBACK_WINDOW],
+    inputs: [u8; ROLLBACK_WINDOW],
+    /// The latest confirmed input, used as the prediction.
+    last_input: u8,
+    rollbacks: u32,
+}
+
+impl RollbackSession {
+    pub fn new(sim: Sim) -> Self {
+        let state = sim.snapshot_state();
+        RollbackSession {
+            sim,
+            ticks: 0,
+            confirmed: 0,
+            states: core::array::from_fn(|_| state.clone()),
+            inputs: [0; ROLLBACK_WINDOW],
+            last_input: 0,
+            rollbacks: 0,
+        }
+    }
+
+    /// Advances one tick on the predicted input.
+    pub fn advance(&mut self) -> Result<(), RollbackError> {
+        if self.predicted() as usize >= ROLLBACK_WINDOW {
+            return Err(RollbackError::WindowFull);
+        }
+        let slot = self.ticks as usize % ROLLBACK_WINDOW;
+        self.states[slot] = self.sim.snapshot_state();
+        self.inputs[slot] = self.last_input;
+        self.sim.tick(self.last_input);
+        self.ticks += 1;
+        Ok(())
+    }
+
+    /// Confirms the real input of the oldest unconfirmed tick. On a
+    /// misprediction, rewinds to before that tick and re-simulates every
+    /// later tick on the new prediction; returns whether it did. Events
+    /// from the re-simulated ticks are dropped, so drain the sim first.
+    pub fn confirm(&mut self, input: u8) -> Result<bool, RollbackError> {
+        if self.confirmed == self.ticks {
+            return Err(RollbackError::NothingToConfirm);
+        }
+        let first = self.confirmed;
+        self.confirmed += 1;
+        self.last_input = input;
+        let slot = first as usize % ROLLBACK_WINDOW;
+        if self.inputs[slot] == input {
+            return Ok(false);
+        }
+        self.rollbacks += 1;
+        self.inputs[slot] = input;
+        self.sim.restore_state(&self.states[slot]);
+        self.sim.tick(input);
+        for t in first + 1..self.ticks {
+            let slot = t as usize % ROLLBACK_WINDOW;
+            self.states[slot] = self.sim.snapshot_state();
+            self.inputs[slot] = input;
+            self.sim.tick(input);
+        }
+        self.sim.drain_events().for_each(drop);
+        Ok(true)
+    }
+
+    /// The sim at the present tick, partly predicted.
+    pub fn sim(&self) -> &Sim {
+        &self.sim
+    }
+
+    /// The sim, for draining its events.
+    pub fn sim_mut(&mut self) -> &mut Sim {
+        &mut self.sim
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn confirmed(&self) -> u32 {
+        self.confirmed
+    }
+
+    /// Ticks run ahead on predicted input.
+    pub fn predicted(&self) -> u32 {
+        self.ticks - self.confirmed
+    }
+
+    /// Mispredictions so far.
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{INPUT_FIRE, INPUT_JUMP};
+
+    #[test]
+    fn restored_states_replay_identically() {
+        let mut sim = Sim::new(4);
+        sim.hash_states();
+        for t in 0..90u32 {
+            sim.tick(if t % 30 == 0 { INPUT_JUMP } else { 0 });
+        }
+        let saved = sim.snapshot_state();
+        let mut ahead = sim.clone();
+        for _ in 0..40 {
+            ahead.tick(INPUT_JUMP | INPUT_FIRE);
+        }
+        let expected = ahead.state_hash();
+        sim.tick(0);
+        sim.restore_state(&saved);
+        assert!(sim.events().is_empty());
+        for _ in 0..40 {
+            sim.tick(INPUT_JUMP | INPUT_FIRE);
+        }
+        assert_eq!(sim.state_hash(), expected);
+    }
+
+    #[test]
+    fn rollbacks_converge_on_the_real_inputs() {
+        let real: [u8; 600] = core::array::from_fn(|t| if t % 35 < 3 { INPUT_JUMP } else { 0 });
+        let mut sim = Sim::new(5);
+        sim.hash_states();
+        let mut session = RollbackSession::new(sim.clone());
+        let mut truth = sim;
+        // Inputs arrive three ticks late.
+        for (t, &input) in real.iter().enumerate() {
+            session.advance().unwrap();
+            if t >= 3 {
+                session.confirm(real[t - 3]).unwrap();
+            }
+            truth.tick(input);
+        }
+        while session.predicted() > 0 {
+            session.confirm(real[session.confirmed() as usize]).unwrap();
+        }
+        assert_eq!(session.sim().state_hash(), truth.state_hash());
+        assert!(session.rollbacks() > 0);
+        assert_eq!(session.confirm(0), Err(RollbackError::NothingToConfirm));
+        for _ in 0..ROLLBACK_WINDOW {
+            session.advance().unwrap();
+        }
+        assert_eq!(session.advance(), Err(RollbackError::WindowFull));
+    }
+}
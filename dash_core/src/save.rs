@@ -0,0 +1,675 @@
+//! Versioned binary save state for [`Sim`].
+//!
+//! Layout (little-endian): `b"DSAV"`, a version byte, the scalar state in
+//! field order, then each entity array as an active-slot bitmask followed by
+//! the active entries in slot order. Inactive slots carry no state (spawns
+//! overwrite every field), so restoring them as defaults keeps the sim
+//! bit-for-bit identical to an uninterrupted run. Undrained
+//! [`crate::GameEvent`]s are output, not state, and are not saved.
+
+use crate::*;
+use dario_levels::ChunkState;
+
+/// Save format magic.
+pub const SAVE_MAGIC: [u8; 4] = *b"DSAV";
+/// Current save format version. Bump on any layout change.
+pub const SAVE_VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 5;
+/// [`SimConfig`] tuning words; the rule variants ride in flags and state.
+const CONFIG_LEN: usize = 14 * 4;
+const SCALARS_LEN: usize = 8
+    + 8
+    + 8
+    + 4
+    + 4
+    + 2
+    + 1
+    + 4
+    + 4
+    + 1
+    + 2
+    + 4 * 8
+    + 4
+    + 4
+    + 8
+    + 4
+    + 4
+    + 4
+    + 4
+    + 1
+    + 4
+    + 4
+    + 4
+    + 4
+    + CHUNKER_LEN
+    + 4 * 4;
+const OBSTACLE_LEN: usize = 4 + 1 + 4 + 4 + 2 + 2 + 4 + 4;
+const ITEM_LEN: usize = 4 + 1 + 4 + 4;
+const CHUNKER_LEN: usize = 8 + 4 + 1 + 1 + 4 + 1;
+const FIREBALL_LEN: usize = 4 + 4 + 4;
+const COIN_LEN: usize = 4 + 4 + 4;
+const BOSS_LEN: usize = 4 + 4 + 4 + 1 + 1 + 4 + 4;
+const BOSS_SHOT_LEN: usize = 4 + 4 + 4;
+
+/// Upper bound on the encoded size of any [`Sim`].
+pub const SAVE_CAP: usize = HEADER_LEN
+    + CONFIG_LEN
+    + SCALARS_LEN
+    + 2
+    + MAX_OBSTACLES * OBSTACLE_LEN
+    + 1
+    + MAX_ITEMS * ITEM_LEN
+    + 1
+    + MAX_FIREBALLS * FIREBALL_LEN
+    + 4
+    + MAX_COINS * COIN_LEN
+    + 1
+    + BOSS_LEN
+    + 1
+    + MAX_BOSS_SHOTS * BOSS_SHOT_LEN;
+
+const FLAG_OVER: u16 = 1;
+const FLAG_GROUNDED: u16 = 2;
+const FLAG_RESERVE: u16 = 4;
+const FLAG_EXPIRY: u16 = 8;
+const FLAG_HEARTS: u16 = 16;
+const FLAG_HAZARDS: u16 = 32;
+const FLAG_COINS: u16 = 64;
+const FLAG_CHUNKS: u16 = 128;
+const FLAG_BOSSES: u16 = 256;
+const FLAG_HASHING: u16 = 512;
+
+/// Why a save could not be written or read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaveError {
+    /// The output buffer is shorter than the encoding (see [`SAVE_CAP`]).
+    BufferTooSmall,
+    /// The input does not start with [`SAVE_MAGIC`].
+    BadMagic,
+    /// The input was written by an unknown format version.
+    UnsupportedVersion(u8),
+    /// The input ends before the encoding does.
+    Truncated,
+    /// A field holds a value no `Sim` can be in.
+    Invalid,
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), SaveError> {
+        let end = self.pos + bytes.len();
+        self.buf
+            .get_mut(self.pos..end)
+            .ok_or(SaveError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn u8(&mut self, v: u8) -> Result<(), SaveError> {
+        self.bytes(&[v])
+    }
+
+    fn u16(&mut self, v: u16) -> Result<(), SaveError> {
+        self.bytes(&v.to_le_bytes())
+    }
+
+    fn u32(&mut self, v: u32) -> Result<(), SaveError> {
+        self.bytes(&v.to_le_bytes())
+    }
+
+    fn i32(&mut self, v: i32) -> Result<(), SaveError> {
+        self.bytes(&v.to_le_bytes())
+    }
+
+    fn u64(&mut self, v: u64) -> Result<(), SaveError> {
+        self.bytes(&v.to_le_bytes())
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], SaveError> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + N)
+            .ok_or(SaveError::Truncated)?;
+        self.pos += N;
+        let mut out = [0u8; N];
+        out.copy_from_slice(bytes);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, SaveError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SaveError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, SaveError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, SaveError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, SaveError> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+}
+
+impl Sim {
+    /// Encodes the full state into `out`, returning the number of bytes
+    /// written (at most [`SAVE_CAP`]).
+    pub fn save_to(&self, out: &mut [u8]) -> Result<usize, SaveError> {
+        let mut w = Writer { buf: out, pos: 0 };
+        w.bytes(&SAVE_MAGIC)?;
+        w.u8(SAVE_VERSION)?;
+
+        let mut flags = 0;
+        for (set, flag) in [
+            (self.over, FLAG_OVER),
+            (self.grounded, FLAG_GROUNDED),
+            (self.config.reserve, FLAG_RESERVE),
+            (self.config.form_expiry.is_some(), FLAG_EXPIRY),
+            (self.hearts.is_some(), FLAG_HEARTS),
+            (self.config.hazards, FLAG_HAZARDS),
+            (self.config.coins, FLAG_COINS),
+            (self.config.chunks, FLAG_CHUNKS),
+            (self.config.bosses, FLAG_BOSSES),
+            (self.hashing, FLAG_HASHING),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        let c = &self.config;
+        for word in [
+            c.gravity,
+            c.jump_v,
+            c.super_jump_v,
+            c.glide_fall_cap,
+            c.base_speed,
+            c.max_speed,
+            c.speed_ramp,
+        ] {
+            w.i32(word)?;
+        }
+        for word in [
+            c.gap_min,
+            c.gap_range,
+            c.gap_pad,
+            c.item_gap_min,
+            c.item_gap_range,
+            c.invuln_ticks,
+            c.fireball_cooldown,
+        ] {
+            w.u32(word)?;
+        }
+        w.u64(self.seed)?;
+        w.u64(self.rng.0)?;
+        w.u64(self.state_hash)?;
+        w.u32(self.ticks)?;
+        w.u32(self.next_id)?;
+        w.u16(flags)?;
+        w.u8(self.form as u8)?;
+        w.u32(self.form_ticks)?;
+        w.u32(self.config.form_expiry.unwrap_or(0))?;
+        w.u8(self.reserve.map_or(u8::MAX, |item| item as u8))?;
+        let hearts = self.hearts.unwrap_or(Hearts { left: 0, max: 0 });
+        w.bytes(&[hearts.left, hearts.max])?;
+        let score = &self.score;
+        for part in [score.pickups, score.kills, score.coins, score.bosses] {
+            w.u64(part)?;
+        }
+        w.u32(self.pickups)?;
+        w.u32(self.kills)?;
+        w.u64(self.distance as u64)?;
+        w.i32(self.player_y)?;
+        w.i32(self.player_vy)?;
+        w.u32(self.invuln)?;
+        w.u32(self.fire_cd)?;
+        w.u8(self.prev_input)?;
+        w.u32(self.spawn_in)?;
+        w.u32(self.item_in)?;
+        w.u32(self.coins)?;
+        w.u32(self.coin_in)?;
+        let chunker = self.chunker.state();
+        w.u64(chunker.rng)?;
+        w.u32(chunker.chunk)?;
+        w.bytes(&[chunker.biome, chunker.pattern])?;
+        w.u32(chunker.start_px)?;
+        w.u8(chunker.next_slot)?;
+        w.u32(self.bosses)?;
+        w.u32(self.boss_at)?;
+        w.u32(self.boss_hits)?;
+        w.u32(self.bosses_defeated)?;
+
+        let mask = self
+            .obstacles
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.active)
+            .fold(0u16, |m, (i, _)| m | 1 << i);
+        w.u16(mask)?;
+        for o in self.obstacles.iter().filter(|o| o.active) {
+            w.u32(o.id)?;
+            w.u8(o.kind as u8)?;
+            w.i32(o.x)?;
+            w.i32(o.y)?;
+            w.u16(o.w as u16)?;
+            w.u16(o.h as u16)?;
+            w.i32(o.base_y)?;
+            w.u32(o.phase)?;
+        }
+        let mask = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, it)| it.active)
+            .fold(0u8, |m, (i, _)| m | 1 << i);
+        w.u8(mask)?;
+        for it in self.items.iter().filter(|it| it.active) {
+            w.u32(it.id)?;
+            w.u8(it.kind as u8)?;
+            w.i32(it.x)?;
+            w.i32(it.y)?;
+        }
+        let mask = self
+            .fireballs
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.active)
+            .fold(0u8, |m, (i, _)| m | 1 << i);
+        w.u8(mask)?;
+        for f in self.fireballs.iter().filter(|f| f.active) {
+            w.u32(f.id)?;
+            w.i32(f.x)?;
+            w.i32(f.y)?;
+        }
+        let mask = self
+            .coin_slots
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.active)
+            .fold(0u32, |m, (i, _)| m | 1 << i);
+        w.u32(mask)?;
+        for c in self.coin_slots.iter().filter(|c| c.active) {
+            w.u32(c.id)?;
+            w.i32(c.x)?;
+            w.i32(c.y)?;
+        }
+        let b = &self.boss;
+        w.u8(u8::from(b.active))?;
+        if b.active {
+            w.u32(b.id)?;
+            w.i32(b.x)?;
+            w.i32(b.y)?;
+            w.bytes(&[b.hp, b.max_hp])?;
+            w.u32(b.ticks)?;
+            w.u32(b.thrown)?;
+        }
+        let mask = self
+            .boss_shots
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.active)
+            .fold(0u8, |m, (i, _)| m | 1 << i);
+        w.u8(mask)?;
+        for s in self.boss_shots.iter().filter(|s| s.active) {
+            w.u32(s.id)?;
+            w.i32(s.x)?;
+            w.i32(s.y)?;
+        }
+        Ok(w.pos)
+    }
+
+    /// Decodes a state written by [`Sim::save_to`]. Ticking the result
+    /// continues exactly like the sim that was saved.
+    pub fn load_from(bytes: &[u8]) -> Result<Sim, SaveError> {
+        let mut r = Reader { buf: bytes, pos: 0 };
+        if r.take::<4>().map_err(|_| SaveError::BadMagic)? != SAVE_MAGIC {
+            return Err(SaveError::BadMagic);
+        }
+        match r.u8()? {
+            SAVE_VERSION => {}
+            v => return Err(SaveError::UnsupportedVersion(v)),
+        }
+
+        let mut c = SimConfig::NORMAL;
+        for word in [
+            &mut c.gravity,
+            &mut c.jump_v,
+            &mut c.super_jump_v,
+            &mut c.glide_fall_cap,
+            &mut c.base_speed,
+            &mut c.max_speed,
+            &mut c.speed_ramp,
+        ] {
+            *word = r.i32()?;
+        }
+        for word in [
+            &mut c.gap_min,
+            &mut c.gap_range,
+            &mut c.gap_pad,
+            &mut c.item_gap_min,
+            &mut c.item_gap_range,
+            &mut c.invuln_ticks,
+            &mut c.fireball_cooldown,
+        ] {
+            *word = r.u32()?;
+        }
+
+        let mut sim = Sim::new(0);
+        sim.seed = r.u64()?;
+        sim.rng = Rng(r.u64()?);
+        sim.state_hash = r.u64()?;
+        sim.ticks = r.u32()?;
+        sim.next_id = r.u32()?;
+        let flags = r.u16()?;
+        sim.over = flags & FLAG_OVER != 0;
+        sim.grounded = flags & FLAG_GROUNDED != 0;
+        sim.hashing = flags & FLAG_HASHING != 0;
+        c.reserve = flags & FLAG_RESERVE != 0;
+        c.hazards = flags & FLAG_HAZARDS != 0;
+        c.coins = flags & FLAG_COINS != 0;
+        c.chunks = flags & FLAG_CHUNKS != 0;
+        c.bosses = flags & FLAG_BOSSES != 0;
+        sim.form = *DarioState::ALL
+            .get(usize::from(r.u8()?))
+            .ok_or(SaveError::Invalid)?;
+        sim.form_ticks = r.u32()?;
+        let expiry = r.u32()?;
+        c.form_expiry = (flags & FLAG_EXPIRY != 0).then_some(expiry);
+        sim.reserve = match r.u8()? {
+            u8::MAX => None,
+            item => Some(PowerUp::try_from(u32::from(item)).map_err(|_| SaveError::Invalid)?),
+        };
+        let [left, max] = r.take::<2>()?;
+        sim.hearts = (flags & FLAG_HEARTS != 0).then_some(Hearts { left, max });
+        if sim.hearts.is_some() && left > max {
+            return Err(SaveError::Invalid);
+        }
+        c.hearts = sim.hearts.map(|h| h.max);
+        c.validate().map_err(|_| SaveError::Invalid)?;
+        sim.config = c;
+        let [pickups, kills, coins, bosses] = [r.u64()?, r.u64()?, r.u64()?, r.u64()?];
+        sim.pickups = r.u32()?;
+        sim.kills = r.u32()?;
+        sim.distance = r.u64()? as i64;
+        sim.score = ScoreBreakdown {
+            distance: (sim.distance / (i64::from(FP) * 50)) as u64,
+            pickups,
+            kills,
+            coins,
+            bosses,
+            capped_by: 0,
+        };
+        sim.player_y = r.i32()?;
+        sim.player_vy = r.i32()?;
+        sim.invuln = r.u32()?;
+        sim.fire_cd = r.u32()?;
+        sim.prev_input = r.u8()?;
+        sim.spawn_in = r.u32()?;
+        sim.item_in = r.u32()?;
+        sim.coins = r.u32()?;
+        sim.coin_in = r.u32()?;
+        if c.coins && sim.coin_in == 0 {
+            return Err(SaveError::Invalid);
+        }
+        let rng = r.u64()?;
+        let chunk = r.u32()?;
+        let [biome, pattern] = r.take::<2>()?;
+        sim.chunker = Chunker::from_state(ChunkState {
+            rng,
+            chunk,
+            biome,
+            pattern,
+            start_px: r.u32()?,
+            next_slot: r.u8()?,
+        })
+        .ok_or(SaveError::Invalid)?;
+        sim.bosses = r.u32()?;
+        sim.boss_at = r.u32()?;
+        sim.boss_hits = r.u32()?;
+        sim.bosses_defeated = r.u32()?;
+
+        let mask = r.u16()?;
+        for (i, o) in sim.obstacles.iter_mut().enumerate() {
+            if mask & 1 << i == 0 {
+                continue;
+            }
+            *o = Obstacle {
+                active: true,
+                id: r.u32()?,
+                kind: i32::from(r.u8()?),
+                x: r.i32()?,
+                y: r.i32()?,
+                w: i32::from(r.u16()?),
+                h: i32::from(r.u16()?),
+                base_y: r.i32()?,
+                phase: r.u32()?,
+            };
+        }
+        let mask = r.u8()?;
+        for (i, it) in sim.items.iter_mut().enumerate() {
+            if mask & 1 << i == 0 {
+                continue;
+            }
+            *it = Item {
+                active: true,
+                id: r.u32()?,
+                kind: i32::from(r.u8()?),
+                x: r.i32()?,
+                y: r.i32()?,
+            };
+        }
+        let mask = r.u8()?;
+        if usize::from(mask) >> MAX_FIREBALLS != 0 {
+            return Err(SaveError::Invalid);
+        }
+        for (i, f) in sim.fireballs.iter_mut().enumerate() {
+            if mask & 1 << i == 0 {
+                continue;
+            }
+            *f = Fireball {
+                active: true,
+                id: r.u32()?,
+                x: r.i32()?,
+                y: r.i32()?,
+            };
+        }
+        let mask = r.u32()?;
+        if mask >> MAX_COINS != 0 {
+            return Err(SaveError::Invalid);
+        }
+        for (i, c) in sim.coin_slots.iter_mut().enumerate() {
+            if mask & 1 << i == 0 {
+                continue;
+            }
+            *c = Coin {
+                active: true,
+                id: r.u32()?,
+                x: r.i32()?,
+                y: r.i32()?,
+            };
+        }
+        match r.u8()? {
+            0 => {}
+            1 => {
+                let id = r.u32()?;
+                let x = r.i32()?;
+                let y = r.i32()?;
+                let [hp, max_hp] = r.take::<2>()?;
+                if hp == 0 || hp > max_hp {
+                    return Err(SaveError::Invalid);
+                }
+                sim.boss = Boss {
+                    active: true,
+                    id,
+                    x,
+                    y,
+                    hp,
+                    max_hp,
+                    ticks: r.u32()?,
+                    thrown: r.u32()?,
+                };
+            }
+            _ => return Err(SaveError::Invalid),
+        }
+        let mask = r.u8()?;
+        if usize::from(mask) >> MAX_BOSS_SHOTS != 0 {
+            return Err(SaveError::Invalid);
+        }
+        for (i, s) in sim.boss_shots.iter_mut().enumerate() {
+            if mask & 1 << i == 0 {
+                continue;
+            }
+            *s = BossShot {
+                active: true,
+                id: r.u32()?,
+                x: r.i32()?,
+                y: r.i32()?,
+            };
+        }
+        Ok(sim)
+    }
+}
+
+/// Whether the scroll speed stays a positive `i32` for a whole run; spawn
+/// timing divides by it. The unclamped speed is linear in the tick, so
+/// checking both ends of the run covers every tick in between.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(i: usize) -> u8 {
+        let mut b = if i % 50 < 12 { INPUT_JUMP } else { 0 };
+        if i.is_multiple_of(7) {
+            b |= INPUT_FIRE | INPUT_USE;
+        }
+        b
+    }
+
+    #[test]
+    fn restore_continues_bit_for_bit() {
+        for mut sim in [
+            Sim::new(42),
+            Sim::with_reserve(8),
+            Sim::with_hearts(5, 3),
+            Sim::with_config(11, SimConfig::HARD).unwrap(),
+            Sim::with_hazards(13),
+            Sim::with_coins(17),
+            Sim::with_chunks(19),
+            Sim::with_config(
+                23,
+                SimConfig {
+                    bosses: true,
+                    hearts: Some(20),
+                    ..SimConfig::NORMAL
+                },
+            )
+            .unwrap(),
+        ] {
+            sim.hash_states();
+            for i in 0..700 {
+                sim.tick(input(i));
+            }
+            let mut buf = [0u8; SAVE_CAP];
+            let n = sim.save_to(&mut buf).unwrap();
+            let mut restored = Sim::load_from(&buf[..n]).unwrap();
+
+            for i in 700..2500 {
+                sim.tick(input(i));
+                restored.tick(input(i));
+            }
+            let mut a = [0u8; SAVE_CAP];
+            let mut b = [0u8; SAVE_CAP];
+            let na = sim.save_to(&mut a).unwrap();
+            let nb = restored.save_to(&mut b).unwrap();
+            assert_eq!(a[..na], b[..nb]);
+            assert_eq!(sim.score_breakdown(), restored.score_breakdown());
+            assert_eq!(sim.ticks(), restored.ticks());
+            assert_eq!(sim.state_hash(), restored.state_hash());
+            assert_ne!(sim.state_hash(), 0);
+        }
+    }
+
+    #[test]
+    fn rejects_foreign_and_short_input() {
+        let sim = Sim::new(1);
+        let mut buf = [0u8; SAVE_CAP];
+        let n = sim.save_to(&mut buf).unwrap();
+        assert_eq!(
+            sim.save_to(&mut [0u8; 8]).unwrap_err(),
+            SaveError::BufferTooSmall
+        );
+        assert_eq!(Sim::load_from(b"nope").err(), Some(SaveError::BadMagic));
+        assert_eq!(
+            Sim::load_from(&buf[..n - 1]).err(),
+            Some(SaveError::Truncated)
+        );
+        buf[4] = SAVE_VERSION + 1;
+        assert_eq!(
+            Sim::load_from(&buf[..n]).err(),
+            Some(SaveError::UnsupportedVersion(SAVE_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn rejects_states_that_cannot_run() {
+        let stalled = [
+            SimConfig {
+                base_speed: 0,
+                speed_ramp: 0,
+                ..SimConfig::NORMAL
+            },
+            SimConfig {
+                max_speed: 0,
+                ..SimConfig::NORMAL
+            },
+            SimConfig {
+                speed_ramp: -10,
+                ..SimConfig::NORMAL
+            },
+        ];
+        let mut sims = stalled.map(|config| {
+            let mut sim = Sim::new(1);
+            sim.config = config;
+            sim
+        });
+        let mut broken_hearts = Sim::with_hearts(1, 3);
+        broken_hearts.hearts = Some(Hearts { left: 4, max: 3 });
+        let mut no_hearts = Sim::with_hearts(1, 3);
+        no_hearts.hearts = Some(Hearts { left: 0, max: 0 });
+        for sim in sims.iter_mut().chain([&mut broken_hearts, &mut no_hearts]) {
+            let mut buf = [0u8; SAVE_CAP];
+            let n = sim.save_to(&mut buf).unwrap();
+            assert_eq!(Sim::load_from(&buf[..n]).err(), Some(SaveError::Invalid));
+        }
+        // A slowing ramp is fine while the speed stays positive.
+        let slowing = Sim::with_config(
+            1,
+            SimConfig {
+                speed_ramp: -1,
+                ..SimConfig::NORMAL
+            },
+        )
+        .unwrap();
+        let mut buf = [0u8; SAVE_CAP];
+        let n = slowing.save_to(&mut buf).unwrap();
+        assert!(Sim::load_from(&buf[..n]).is_ok());
+    }
+}
@@ -0,0 +1,172 @@
+//! Local split-screen matches: two live players on one seed.
+//!
+//! A ranked world is a function of the seed and the tick alone: spawn
+//! timing, kinds and positions never depend on what Dario does. So a
+//! [`Match`] is simply one ranked [`Sim`] per player, ticked in lockstep
+//! with that player's input. Both see the same obstacles, items and coins;
+//! physics, form, kills, pickups and score are their own. The one
+//! exception is a player whose slot table runs out (see
+//! [`Sim::exhausted`]): the skipped spawn also skips its random rolls, so
+//! that player's world diverges from then on.
+//!
+//! Since each player's run is exactly `Sim::new(seed)` plus their own
+//! inputs, either one can later prove their half of a match on its own.
+//! A match record interleaves the two traces, two bytes per tick
+//! (player 0 first); [`player_trace`] splits one back out.
+
+use crate::{RunResult, Sim, MAX_TICKS};
+use core::cmp::Ordering;
+
+/// Players per match.
+pub const PLAYERS: usize = 2;
+
+/// Two live ranked runs on one seed.
+pub struct Match {
+    seed: u64,
+    players: [Sim; PLAYERS],
+}
+
+impl Match {
+    pub fn new(seed: u64) -> Self {
+        Match {
+            seed,
+            players: [Sim::new(seed), Sim::new(seed)],
+        }
+    }
+
+    /// Advances both players one tick, `inputs[p]` for player `p`.
+    /// Players whose run is over stay put.
+    pub fn tick(&mut self, inputs: [u8; PLAYERS]) {
+        for (sim, input) in self.players.iter_mut().zip(inputs) {
+            if !sim.over() {
+                sim.tick(input);
+            }
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Lockstep ticks so far, i.e. the longer run's length.
+    pub fn ticks(&self) -> u32 {
+        self.players.iter().map(Sim::ticks).max().unwrap_or(0)
+    }
+
+    /// Player `p`'s run. Panics if `p >= PLAYERS`.
+    pub fn player(&self, p: usize) -> &Sim {
+        &self.players[p]
+    }
+
+    /// Player `p`'s run, for draining its events.
+    pub fn player_mut(&mut self, p: usize) -> &mut Sim {
+        &mut self.players[p]
+    }
+
+    /// Whether both runs are over.
+    pub fn over(&self) -> bool {
+        self.players.iter().all(Sim::over)
+    }
+
+    /// The player with the higher score once both runs are over; `None`
+    /// while the match is on or on a tie.
+    pub fn winner(&self) -> Option<usize> {
+        if !self.over() {
+            return None;
+        }
+        match self.players[0].score().cmp(&self.players[1].score()) {
+            Ordering::Greater => Some(0),
+            Ordering::Less => Some(1),
+            Ordering::Equal => None,
+        }
+    }
+}
+
+/// Player `p`'s inputs from a match record, one byte per tick. Inputs
+/// recorded after that player's run ended are ignored by
+/// [`replay`](crate::replay).
+pub fn player_trace(record: &[u8], p: usize) -> impl Iterator<Item = u8> + '_ {
+    record.iter().skip(p).step_by(PLAYERS).copied()
+}
+
+/// The verified outcome of a match record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchResult {
+    pub results: [RunResult; PLAYERS],
+    /// As [`Match::winner`].
+    pub winner: Option<usize>,
+}
+
+/// Replays a match record from `seed`, like [`replay`](crate::replay) for
+/// each player. A trailing odd byte is ignored.
+pub fn replay_match(seed: u64, record: &[u8]) -> MatchResult {
+    let mut game = Match::new(seed);
+    for pair in record.chunks_exact(PLAYERS).take(MAX_TICKS as usize) {
+        if game.over() {
+            break;
+        }
+        game.tick([pair[0], pair[1]]);
+    }
+    let results = game.players.each_ref().map(|sim| RunResult {
+        score: sim.score(),
+        ticks: sim.ticks(),
+        over: sim.over(),
+    });
+    MatchResult {
+        results,
+        winner: game.winner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{replay, INPUT_JUMP};
+
+    #[test]
+    fn each_half_of_a_match_replays_alone() {
+        let mut record = [0u8; 2 * 2000];
+        for (t, pair) in record.chunks_exact_mut(PLAYERS).enumerate() {
+            if t % 35 == 0 {
+                pair[1] = INPUT_JUMP;
+            }
+        }
+        let mut game = Match::new(5);
+        for pair in record.chunks_exact(PLAYERS) {
+            game.tick([pair[0], pair[1]]);
+        }
+        let outcome = replay_match(5, &record);
+        for p in 0..PLAYERS {
+            let mut trace = [0u8; 2000];
+            for (slot, input) in trace.iter_mut().zip(player_trace(&record, p)) {
+                *slot = input;
+            }
+            let alone = replay(5, &trace);
+            assert_eq!(outcome.results[p], alone);
+            assert_eq!(game.player(p).score(), alone.score);
+        }
+        // The idle player dies first; the hopper outlasts them.
+        assert!(outcome.results[0].ticks < outcome.results[1].ticks);
+        assert_eq!(outcome.winner, Some(1));
+        assert_eq!(game.winner(), Some(1));
+    }
+
+    #[test]
+    fn players_share_the_world_until_they_touch_it() {
+        let mut game = Match::new(12);
+        for p in 0..PLAYERS {
+            game.player_mut(p).hash_states();
+        }
+        for _ in 0..120 {
+            game.tick([0, 0]);
+        }
+        let (a, b) = (game.player(0), game.player(1));
+        assert!(a.entities().eq(b.entities()));
+        assert_eq!(a.state_hash(), b.state_hash());
+        game.tick([INPUT_JUMP, 0]);
+        let (a, b) = (game.player(0), game.player(1));
+        assert_ne!(a.player_px(), b.player_px());
+        assert!(a.entities().eq(b.entities()));
+        assert_eq!(game.winner(), None);
+    }
+}
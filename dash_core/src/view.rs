@@ -0,0 +1,23 @@
+//! Typed render views of the sim's entities.
+//!
+//! Positions and sizes are in pixels. Every entity gets an id when it
+//! spawns that it keeps until it leaves the world, and ids are never reused
+//! within a run, so renderers can match entities across frames for
+//! interpolation and animation.
+
+pub use dario_view::*;
+
+/// A ghost in a [`crate::ghost::GhostRace`], drawn like the player:
+/// `x`/`y` is the left edge and feet, with `x` shifted by how far the ghost
+/// has scrolled ahead of (or behind) the live run. `id` is its slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ghost {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    /// `DarioState` as `u32`, like [`crate::Sim::form`].
+    pub form: u32,
+    pub alive: bool,
+}
@@ -0,0 +1,194 @@
+//! Seed difficulty reports for ranked `dash_zk` runs.
+//!
+//! Players pick their seed, so some seeds are easier than others. A
+//! [`SeedReport`] summarizes a seed's [`Schedule`]: obstacle density, the
+//! tightest gap between ground obstacles, bats that no jump clears (and of
+//! those, the ones that also hit Dario on the ground) and items per kind,
+//! plus optionally the best score the [`crate::solve`]r reaches.
+
+use crate::{solve, SolverConfig};
+use alloc::string::String;
+use core::fmt::Write;
+use dash_zk::{
+    bat_hitbox, jump_disp, jump_landing, BatObs, Schedule, ZkSim, FP, GROUND_Y, JUMP_V, MAX_TICKS,
+    PLAYER_BOX_BOT_FP, PLAYER_BOX_TOP_FP, PLAYER_LEFT100, PLAYER_RIGHT100, TICK_HZ,
+};
+
+/// The solver's best run on a seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reach {
+    pub score: u64,
+    pub ticks: u32,
+    pub survived: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeedReport {
+    pub seed: u64,
+    pub ground: u32,
+    pub bats: u32,
+    /// Obstacles (ground and bats) per second of the 2-minute schedule.
+    pub density: f64,
+    /// Fewest ticks between two consecutive ground obstacle spawns.
+    pub min_ground_gap: Option<u32>,
+    /// Bats that every Regular-form jump hits, over or under: they must be
+    /// passed on the ground, which makes ground obstacles near them hard.
+    pub unjumpable_bats: u32,
+    /// Unjumpable bats that also hit Dario running on the ground: only a
+    /// fireball, a power-up or invulnerability gets past them unharmed.
+    pub unavoidable_bats: u32,
+    /// Scheduled items by kind: espresso, chili, cape.
+    pub items: [u32; 3],
+    pub best: Option<Reach>,
+}
+
+impl SeedReport {
+    pub const CSV_HEADER: &'static str = "seed,ground,bats,density,min_ground_gap,\
+        unjumpable_bats,unavoidable_bats,espresso,chili,cape,best_score,best_ticks,survived";
+
+    /// One CSV line matching [`SeedReport::CSV_HEADER`]; missing values
+    /// are left empty.
+    pub fn csv_row(&self) -> String {
+        let mut row = String::new();
+        let _ = write!(
+            row,
+            "{},{},{},{:.3},",
+            self.seed, self.ground, self.bats, self.density
+        );
+        if let Some(gap) = self.min_ground_gap {
+            let _ = write!(row, "{gap}");
+        }
+        let [espresso, chili, cape] = self.items;
+        let _ = write!(
+            row,
+            ",{},{},{espresso},{chili},{cape},",
+            self.unjumpable_bats, self.unavoidable_bats
+        );
+        if let Some(best) = self.best {
+            let _ = write!(row, "{},{},{}", best.score, best.ticks, best.survived);
+        } else {
+            row.push_str(",,");
+        }
+        row
+    }
+}
+
+/// Reports on `seed`'s ranked schedule, running the solver with `solver`
+/// for [`SeedReport::best`] when given.
+pub fn analyze(seed: u64, solver: Option<&SolverConfig>) -> SeedReport {
+    let sched = Schedule::generate(seed);
+    let ground = &sched.ground[..sched.ground_count];
+    let bats = &sched.bats[..sched.bat_count];
+    let min_ground_gap = ground
+        .windows(2)
+        .map(|w| w[1].spawn_tick - w[0].spawn_tick)
+        .min();
+    let mut unjumpable_bats = 0;
+    let mut unavoidable_bats = 0;
+    for bat in bats {
+        if !jumpable(bat) {
+            unjumpable_bats += 1;
+            if hits_runner(bat) {
+                unavoidable_bats += 1;
+            }
+        }
+    }
+    let mut items = [0; 3];
+    for item in &sched.items[..sched.item_count] {
+        items[item.kind as usize] += 1;
+    }
+    let obstacles = (ground.len() + bats.len()) as f64;
+    SeedReport {
+        seed,
+        ground: ground.len() as u32,
+        bats: bats.len() as u32,
+        density: obstacles / f64::from(MAX_TICKS / TICK_HZ),
+        min_ground_gap,
+        unjumpable_bats,
+        unavoidable_bats,
+        items,
+        best: solver.map(|config| {
+            let solution = solve(&ZkSim::new(seed), config);
+            Reach {
+                score: solution.score,
+                ticks: solution.ticks,
+                survived: solution.survived,
+            }
+        }),
+    }
+}
+
+/// Ticks at which `bat` overlaps the player's column.
+fn passing(bat: &BatObs) -> impl Iterator<Item = u32> + '_ {
+    (bat.spawn_tick..=MAX_TICKS)
+        .skip_while(|&t| bat_hitbox(bat, t).0 >= PLAYER_RIGHT100)
+        .take_while(|&t| bat_hitbox(bat, t).1 > PLAYER_LEFT100)
+}
+
+/// Whether Dario with his feet at `feet_fp` touches `bat` at tick `t`.
+fn touches(bat: &BatObs, t: u32, feet_fp: i32) -> bool {
+    let (_, _, top, bot) = bat_hitbox(bat, t);
+    feet_fp - PLAYER_BOX_TOP_FP < bot && feet_fp - PLAYER_BOX_BOT_FP > top
+}
+
+fn hits_runner(bat: &BatObs) -> bool {
+    passing(bat).any(|t| touches(bat, t, GROUND_Y * FP))
+}
+
+/// Whether some Regular-form jump is airborne and clear of `bat` for its
+/// whole pass.
+fn jumpable(bat: &BatObs) -> bool {
+    let Some(first) = passing(bat).next() else {
+        return true;
+    };
+    let last = passing(bat).last().unwrap_or(first);
+    let land = jump_landing(JUMP_V, false);
+    let earliest = (first + 1).saturating_sub(land).max(1);
+    (earliest..=first).any(|press| {
+        (first..=last).all(|t| {
+            let n = t - press;
+            n >= 1
+                && n < land
+                && !touches(bat, t, GROUND_Y * FP + jump_disp(JUMP_V, false, n) as i32)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_are_deterministic_and_match_the_schedule() {
+        let report = analyze(42, None);
+        assert_eq!(report, analyze(42, None));
+        let sched = Schedule::generate(42);
+        assert_eq!(report.ground as usize, sched.ground_count);
+        assert_eq!(report.bats as usize, sched.bat_count);
+        assert_eq!(report.items.iter().sum::<u32>() as usize, sched.item_count);
+        assert!(report.min_ground_gap.unwrap() > 0);
+        assert!(report.unavoidable_bats <= report.unjumpable_bats);
+        assert!(report.unjumpable_bats <= report.bats);
+
+        let row = report.csv_row();
+        assert_eq!(
+            row.split(',').count(),
+            SeedReport::CSV_HEADER.split(',').count()
+        );
+        assert!(row.starts_with("42,") && row.ends_with(",,,"));
+    }
+
+    #[test]
+    fn low_bats_are_jumpable_and_high_ones_are_not() {
+        let bat = |base_y_px| BatObs {
+            spawn_tick: 100,
+            base_y_px,
+            phase0: 0,
+        };
+        // Sitting on the ground line: a hurdle.
+        assert!(jumpable(&bat(GROUND_Y - 40)) && hits_runner(&bat(GROUND_Y - 40)));
+        // Just above head height: too high to clear, low enough that a
+        // jump runs into it.
+        assert!(!jumpable(&bat(GROUND_Y - 140)) && !hits_runner(&bat(GROUND_Y - 140)));
+    }
+}
@@ -0,0 +1,74 @@
+//! Reports how hard ranked `dash_zk` seeds are.
+//!
+//! Usage: seed_report <seed|daily:day> [--no-solve]
+//!        seed_report <first> <last> [--no-solve] > seeds.csv
+//!
+//! With one seed, prints a readable report. With a range (inclusive), prints
+//! one CSV row per seed for picking fair tournament seeds. Seeds may be
+//! `daily:<day>`; a range of two daily seeds covers the days in between.
+//! `--no-solve` skips the solver's best reachable score, which dominates
+//! the run time.
+
+use dash_solver::analyze::{analyze, SeedReport};
+use dash_solver::SolverConfig;
+use dash_zk::daily::{daily_day, daily_seed, parse_seed, DAILY_DOMAIN};
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let solve = !args.iter().any(|a| a == "--no-solve");
+    args.retain(|a| a != "--no-solve");
+    let seeds: Option<Vec<u64>> = args.iter().map(|a| parse_seed(a)).collect();
+    let config = SolverConfig::default();
+    let solver = solve.then_some(&config);
+    let range = match seeds.as_deref() {
+        Some(&[seed]) => return print_report(&analyze(seed, solver)),
+        Some(&[first, last]) => seed_range(first, last),
+        _ => None,
+    };
+    let Some(range) = range else {
+        eprintln!("usage: seed_report <seed> | <first> <last> [--no-solve]");
+        std::process::exit(1);
+    };
+    println!("{}", SeedReport::CSV_HEADER);
+    for seed in range {
+        println!("{}", analyze(seed, solver).csv_row());
+    }
+}
+
+/// The seeds from `first` to `last` inclusive, or the daily seeds of the
+/// days between when both are daily seeds.
+fn seed_range(first: u64, last: u64) -> Option<Box<dyn Iterator<Item = u64>>> {
+    match (
+        daily_day(first, DAILY_DOMAIN),
+        daily_day(last, DAILY_DOMAIN),
+    ) {
+        (Some(a), Some(b)) if a <= b => {
+            Some(Box::new((a..=b).map(|day| daily_seed(day, DAILY_DOMAIN))))
+        }
+        (None, None) if first <= last => Some(Box::new(first..=last)),
+        _ => None,
+    }
+}
+
+fn print_report(r: &SeedReport) {
+    println!("seed              {}", r.seed);
+    println!("ground obstacles  {}", r.ground);
+    println!("bats              {}", r.bats);
+    println!("density           {:.3} obstacles/s", r.density);
+    match r.min_ground_gap {
+        Some(gap) => println!("min ground gap    {gap} ticks"),
+        None => println!("min ground gap    -"),
+    }
+    println!("unjumpable bats   {}", r.unjumpable_bats);
+    println!("unavoidable bats  {}", r.unavoidable_bats);
+    let [espresso, chili, cape] = r.items;
+    println!("items             {espresso} espresso, {chili} chili, {cape} cape");
+    if let Some(best) = r.best {
+        println!(
+            "best reachable    {} in {} ticks{}",
+            best.score,
+            best.ticks,
+            if best.survived { "" } else { " (died)" }
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Solves a seed with the beam-search autopilot and writes the winning run
+//! as a `.dario` replay (accepted by `export_input` for `zk` runs).
+//!
+//! Usage: solve <zk|core> <seed> <out.dario> [beam_width] [target_score]
+//!
+//! The seed is a number or `daily:<day>` for that day's daily challenge.
+
+use dario_replay::{Replay, RunSummary, SimKind};
+use dash_solver::{solve, Solution, SolverConfig};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 4 {
+        eprintln!("usage: solve <zk|core> <seed> <out.dario> [beam_width] [target_score]");
+        std::process::exit(1);
+    }
+    let seed = dash_zk::daily::parse_seed(&args[2]).expect("seed");
+    let mut config = SolverConfig::default();
+    if let Some(width) = args.get(4) {
+        config.beam_width = width.parse().expect("beam_width");
+    }
+    if let Some(target) = args.get(5) {
+        config.target_score = target.parse().expect("target_score");
+    }
+    let (kind, config_hash, solution): (SimKind, u64, Solution) = match args[1].as_str() {
+        "zk" => {
+            let sim = dash_zk::ZkSim::new(seed);
+            (SimKind::Zk, sim.config_hash(), solve(&sim, &config))
+        }
+        "core" => {
+            let sim = dash_core::Sim::new(seed);
+            (SimKind::Core, sim.config().hash(), solve(&sim, &config))
+        }
+        other => {
+            eprintln!("unknown sim {other:?}, expected zk or core");
+            std::process::exit(1);
+        }
+    };
+
+    eprintln!(
+        "seed={} ticks={} score={} survived={}",
+        seed, solution.ticks, solution.score, solution.survived
+    );
+    let replay = Replay {
+        kind,
+        seed,
+        config_hash,
+        account: None,
+        summary: RunSummary {
+            score: solution.score,
+            ticks: solution.ticks,
+            over: solution.over,
+        },
+        trace: solution.trace,
+    };
+    std::fs::write(&args[3], replay.encode().expect("encode replay")).expect("write output");
+    eprintln!("wrote {}", args[3]);
+}
@@ -4,14 +4,260 @@
 //! recording the input trace so a finished run can be proven and submitted
 //! on-chain.
 
-use dash_core::Sim;
+use dario_replay::{Replay, RunSummary, SimKind, ACCOUNT_LEN};
+use dash_core::ghost::GhostRace;
+use dash_core::versus::{Match, PLAYERS};
+use dash_core::{view, GameEvent, ScoreBreakdown, Sim, SimConfig};
 use dash_zk::ZkSim;
+use std::cell::RefCell;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
+/// `i32`s per entity in the typed-array view: `[id, entity_type, kind, x,
+/// y, w, h]`.
+const VIEW_STRIDE: usize = 1 + view::RECORD_LEN;
+
+/// Fills the fixed-size `out` buffer (allocated once, so its address is
+/// stable) with entity records; returns the entity count.
+fn fill_view(
+    out: &mut Vec<i32>,
+    cap: usize,
+    entities: impl Iterator<Item = view::Entity>,
+) -> usize {
+    out.resize(cap * VIEW_STRIDE, 0);
+    let mut n = 0;
+    for (entity, chunk) in entities.zip(out.chunks_exact_mut(VIEW_STRIDE)) {
+        chunk[0] = entity.id() as i32;
+        chunk[1..].copy_from_slice(&entity.record());
+        n += 1;
+    }
+    n
+}
+
+/// Encodes a run as a `.dario` replay; `account` is empty or 96 bytes.
+fn replay_file(
+    kind: SimKind,
+    seed: u64,
+    config_hash: u64,
+    account: &[u8],
+    summary: RunSummary,
+    trace: &[u8],
+) -> Result<Vec<u8>, JsError> {
+    let account = match account.len() {
+        0 => None,
+        ACCOUNT_LEN => Some(account.try_into().unwrap()),
+        _ => return Err(JsError::new("account must be 96 bytes")),
+    };
+    Replay {
+        kind,
+        seed,
+        config_hash,
+        account,
+        summary,
+        trace: trace.to_vec(),
+    }
+    .encode()
+    .map_err(|e| JsError::new(&format!("cannot encode replay: {e:?}")))
+}
+
+/// `[distance, pickups, kills, coins, bosses, capped_by]`.
+fn breakdown(b: ScoreBreakdown) -> Vec<u64> {
+    vec![
+        b.distance,
+        b.pickups,
+        b.kills,
+        b.coins,
+        b.bosses,
+        b.capped_by,
+    ]
+}
+
+/// `[code, a, b]` record for [`DashSim::drain_events`].
+fn event_record(event: GameEvent) -> [i32; 3] {
+    match event {
+        GameEvent::JumpStarted => [0, 0, 0],
+        GameEvent::Landed => [1, 0, 0],
+        GameEvent::ItemPicked { kind } => [2, kind, 0],
+        GameEvent::FireballShot => [3, 0, 0],
+        GameEvent::ObstacleKilled { kind } => [4, kind, 0],
+        GameEvent::Damaged => [5, 0, 0],
+        GameEvent::FormChanged { from, to } => [6, from as i32, to as i32],
+        GameEvent::GameOver => [7, 0, 0],
+        GameEvent::CoinPicked => [8, 0, 0],
+        GameEvent::BossAppeared { hp } => [9, i32::from(hp), 0],
+        GameEvent::BossHit { hp } => [10, i32::from(hp), 0],
+        GameEvent::BossDefeated => [11, 0, 0],
+        GameEvent::BossEscaped => [12, 0, 0],
+    }
+}
+
+/// Seed of daily challenge `day` (UTC days since the Unix epoch), as the
+/// contract's `daily_seed(day)` query derives it.
+#[wasm_bindgen]
+pub fn daily_seed(day: u32) -> u64 {
+    dash_zk::daily::daily_seed(day, dash_zk::daily::DAILY_DOMAIN)
+}
+
+/// Names of the achievements a `dash_core` run unlocked, replayed from
+/// its seed and [`DashSim::trace`].
+#[wasm_bindgen]
+pub fn core_achievements(seed: u64, trace: &[u8]) -> Vec<String> {
+    achievement_names(dash_achievements::evaluate_core(seed, trace))
+}
+
+/// Names of the achievements a `dash_zk` run unlocked, replayed from its
+/// seed and [`ZkDashSim::trace`].
+#[wasm_bindgen]
+pub fn zk_achievements(seed: u64, trace: &[u8]) -> Vec<String> {
+    achievement_names(dash_achievements::evaluate_zk(seed, trace))
+}
+
+fn achievement_names(unlocked: dash_achievements::Unlocked) -> Vec<String> {
+    unlocked.iter().map(|a| a.name().into()).collect()
+}
+
+/// One `dash_core` run behind a wasm wrapper. [`run_api!`] builds the
+/// per-run accessors on top of it, so every wrapper exposes the same ones.
+trait RunHandle {
+    fn with_run<R>(&self, f: impl FnOnce(&Sim) -> R) -> R;
+    fn with_run_mut<R>(&mut self, f: impl FnOnce(&mut Sim) -> R) -> R;
+}
+
+/// The per-run JS API of a [`RunHandle`]: score, form, counters, the
+/// player rect, the entity snapshot and events.
+macro_rules! run_api {
+    ($handle:ty) => {
+        #[wasm_bindgen]
+        impl $handle {
+            pub fn over(&self) -> bool {
+                self.with_run(Sim::over)
+            }
+
+            pub fn score(&self) -> u64 {
+                self.with_run(Sim::score)
+            }
+
+            /// Where the score came from: [distance, pickups, kills, coins,
+            /// bosses, capped_by]. The first five add up to the score; this
+            /// sim has no cap, so capped_by is 0.
+            pub fn score_breakdown(&self) -> Vec<u64> {
+                self.with_run(|sim| breakdown(sim.score_breakdown()))
+            }
+
+            pub fn ticks(&self) -> u32 {
+                self.with_run(Sim::ticks)
+            }
+
+            /// Current FSM form (DarioState as u32).
+            pub fn form(&self) -> u32 {
+                self.with_run(Sim::form)
+            }
+
+            /// Ticks until the current power-up expires, if it expires at
+            /// all.
+            pub fn form_ticks_left(&self) -> Option<u32> {
+                self.with_run(Sim::form_ticks_left)
+            }
+
+            /// Reserve item kind (0 espresso, 1 chili, 2 cape), if one is
+            /// stored.
+            pub fn reserve(&self) -> Option<u32> {
+                self.with_run(Sim::reserve)
+            }
+
+            /// Hearts left, in hearts mode.
+            pub fn hearts(&self) -> Option<u8> {
+                self.with_run(|sim| sim.hearts().map(|(left, _)| left))
+            }
+
+            /// Maximum hearts, in hearts mode.
+            pub fn max_hearts(&self) -> Option<u8> {
+                self.with_run(|sim| sim.hearts().map(|(_, max)| max))
+            }
+
+            pub fn pickups(&self) -> u32 {
+                self.with_run(Sim::pickups)
+            }
+
+            pub fn kills(&self) -> u32 {
+                self.with_run(Sim::kills)
+            }
+
+            /// Coins collected, in coin mode.
+            pub fn coins(&self) -> u32 {
+                self.with_run(Sim::coins_collected)
+            }
+
+            /// Hit points of the boss on screen, in boss mode.
+            pub fn boss_hp(&self) -> Option<u8> {
+                self.with_run(|sim| sim.boss().map(|b| b.hp))
+            }
+
+            /// Maximum hit points of the boss on screen, in boss mode.
+            pub fn boss_max_hp(&self) -> Option<u8> {
+                self.with_run(|sim| sim.boss().map(|b| b.max_hp))
+            }
+
+            pub fn boss_hits(&self) -> u32 {
+                self.with_run(Sim::boss_hits)
+            }
+
+            pub fn bosses_defeated(&self) -> u32 {
+                self.with_run(Sim::bosses_defeated)
+            }
+
+            pub fn distance_px(&self) -> u64 {
+                self.with_run(Sim::distance_px)
+            }
+
+            /// Current biome (0 meadow, 1 sewer, 2 belfry), in chunked
+            /// levels.
+            pub fn biome(&self) -> Option<u8> {
+                self.with_run(|sim| sim.biome().map(|b| b as u8))
+            }
+
+            pub fn invulnerable(&self) -> bool {
+                self.with_run(Sim::invulnerable)
+            }
+
+            pub fn grounded(&self) -> bool {
+                self.with_run(Sim::grounded)
+            }
+
+            /// Player rect in pixels: [x, y, w, h].
+            pub fn player(&self) -> Vec<i32> {
+                let (x, y, w, h) = self.with_run(Sim::player_px);
+                vec![x, y, w, h]
+            }
+
+            /// Flat entity records: [entity_type, kind, x, y, w, h] per
+            /// entity. entity_type: 0 obstacle, 1 item (kind 3 = heart),
+            /// 2 fireball, 3 coin, 4 boss (kind = hit points left), 5 boss
+            /// shot.
+            pub fn snapshot(&self) -> Vec<i32> {
+                let mut buf = [0i32; Sim::SNAPSHOT_CAP];
+                let n = self.with_run(|sim| sim.snapshot(&mut buf));
+                buf[..n].to_vec()
+            }
+
+            /// Drains the events since the last call as flat `[code, a, b]`
+            /// records: 0 jump started, 1 landed, 2 item picked (a = kind),
+            /// 3 fireball shot, 4 obstacle killed (a = kind), 5 damaged,
+            /// 6 form changed (a = from, b = to), 7 game over, 8 coin
+            /// picked, 9 boss appeared (a = hit points), 10 boss hit (a =
+            /// hit points left), 11 boss defeated, 12 boss escaped.
+            pub fn drain_events(&mut self) -> Vec<i32> {
+                self.with_run_mut(|sim| sim.drain_events().flat_map(event_record).collect())
+            }
+        }
+    };
+}
+
 #[wasm_bindgen]
 pub struct DashSim {
     sim: Sim,
     trace: Vec<u8>,
+    view: Vec<i32>,
 }
 
 #[wasm_bindgen]
@@ -21,10 +267,91 @@ impl DashSim {
         DashSim {
             sim: Sim::new(seed),
             trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant whose power-ups expire after `expire_ticks` ticks.
+    pub fn with_form_expiry(seed: u64, expire_ticks: u32) -> DashSim {
+        DashSim {
+            sim: Sim::with_form_expiry(seed, expire_ticks),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant with a one-slot reserve deployed by bit2.
+    pub fn with_reserve(seed: u64) -> DashSim {
+        DashSim {
+            sim: Sim::with_reserve(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked hearts mode: the run ends when `max_hearts` hearts are lost.
+    pub fn with_hearts(seed: u64, max_hearts: u8) -> DashSim {
+        DashSim {
+            sim: Sim::with_hearts(seed, max_hearts),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant with coin arcs; each coin scores 10 points.
+    pub fn with_coins(seed: u64) -> DashSim {
+        DashSim {
+            sim: Sim::with_coins(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant with ceiling spikes (kind 3) and moving platforms
+    /// (kind 4) among the obstacles.
+    pub fn with_hazards(seed: u64) -> DashSim {
+        DashSim {
+            sim: Sim::with_hazards(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant with boss fights at fixed distances; each fireball
+    /// hit scores 20 points and each boss defeated 250.
+    pub fn with_bosses(seed: u64) -> DashSim {
+        DashSim {
+            sim: Sim::with_bosses(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
         }
     }
 
-    /// Advances one 60Hz tick, recording `input` (bit0 jump, bit1 fire).
+    /// Unranked variant whose obstacles come from hand-authored chunks,
+    /// grouped into biomes (see [`Self::biome`]).
+    pub fn with_chunks(seed: u64) -> DashSim {
+        DashSim {
+            sim: Sim::with_chunks(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked difficulty preset: 0 easy, 2 hard (1 is the ranked
+    /// normal rules, same as the constructor).
+    pub fn with_preset(seed: u64, preset: u8) -> Result<DashSim, JsError> {
+        let config = *SimConfig::PRESETS
+            .get(usize::from(preset))
+            .ok_or_else(|| JsError::new("preset must be 0, 1 or 2"))?;
+        Ok(DashSim {
+            sim: Sim::with_config(seed, config).expect("presets are valid"),
+            trace: Vec::new(),
+            view: Vec::new(),
+        })
+    }
+
+    /// Advances one 60Hz tick, recording `input` (bit0 jump, bit1 fire,
+    /// bit2 use reserve).
     pub fn tick(&mut self, input: u8) {
         if self.sim.over() || self.trace.len() >= dash_core::MAX_TICKS as usize {
             return;
@@ -33,63 +360,338 @@ impl DashSim {
         self.sim.tick(input);
     }
 
-    pub fn over(&self) -> bool {
-        self.sim.over()
+    /// Rolling state hash after the latest tick, for desync bisection
+    /// against a native replay of [`Self::trace`]; 0 until
+    /// [`Self::hash_states`].
+    pub fn state_hash(&self) -> u64 {
+        self.sim.state_hash()
     }
 
-    pub fn score(&self) -> u64 {
-        self.sim.score()
+    /// Turns on [`Self::state_hash`]. Call before the first tick to
+    /// compare against a native replay.
+    pub fn hash_states(&mut self) {
+        self.sim.hash_states();
     }
 
-    pub fn ticks(&self) -> u32 {
-        self.sim.ticks()
+    /// Refreshes the zero-copy entity view and returns the entity count.
+    /// Read it as `new Int32Array(memory.buffer, sim.view_ptr(), 7 * n)`:
+    /// `[id, entity_type, kind, x, y, w, h]` per entity, where ids are
+    /// stable across frames. Re-create the array after wasm memory grows.
+    pub fn refresh_view(&mut self) -> usize {
+        fill_view(&mut self.view, Sim::MAX_ENTITIES, self.sim.entities())
     }
 
-    /// Current FSM form (DarioState as u32).
-    pub fn form(&self) -> u32 {
-        self.sim.form()
+    /// Address of the entity view in wasm memory; valid after
+    /// [`Self::refresh_view`] until the sim is freed.
+    pub fn view_ptr(&self) -> *const i32 {
+        self.view.as_ptr()
     }
 
-    pub fn pickups(&self) -> u32 {
-        self.sim.pickups()
+    /// The recorded input trace (one byte per tick), for proving.
+    pub fn trace(&self) -> Vec<u8> {
+        self.trace.clone()
     }
 
-    pub fn kills(&self) -> u32 {
-        self.sim.kills()
+    /// [`Self::trace`] run-length packed (`dario_replay::trace`); usually
+    /// a few hundred bytes for a full run.
+    pub fn trace_compressed(&self) -> Vec<u8> {
+        let mut packed = Vec::new();
+        dario_replay::trace::encode(&self.trace, &mut packed).expect("trace bytes are masked");
+        packed
     }
 
-    pub fn distance_px(&self) -> u64 {
-        self.sim.distance_px()
+    /// Versioned binary save of the sim state, for suspending a run across
+    /// reloads. Store it alongside [`DashSim::trace`].
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut buf = [0u8; dash_core::SAVE_CAP];
+        let n = self
+            .sim
+            .save_to(&mut buf)
+            .expect("SAVE_CAP bounds every save");
+        buf[..n].to_vec()
     }
 
-    pub fn invulnerable(&self) -> bool {
-        self.sim.invulnerable()
+    /// Resumes a run from [`DashSim::save_state`] bytes and the trace
+    /// recorded up to that point.
+    pub fn load_state(state: &[u8], trace: Vec<u8>) -> Result<DashSim, JsError> {
+        let sim = Sim::load_from(state)
+            .map_err(|e| JsError::new(&format!("invalid save state: {e:?}")))?;
+        if trace.len() != sim.ticks() as usize {
+            return Err(JsError::new("trace length does not match save state"));
+        }
+        Ok(DashSim {
+            sim,
+            trace,
+            view: Vec::new(),
+        })
     }
 
-    pub fn grounded(&self) -> bool {
-        self.sim.grounded()
+    /// The run as a `.dario` replay file for `dash-prover`. `account` is
+    /// the 96-byte Moonlight account, or empty to leave it unbound. The
+    /// config hash is recorded, so unranked runs export too; the prover
+    /// only accepts [`SimConfig::NORMAL`] ones.
+    pub fn replay_file(&self, account: &[u8]) -> Result<Vec<u8>, JsError> {
+        let summary = RunSummary {
+            score: self.sim.score(),
+            ticks: self.sim.ticks(),
+            over: self.sim.over(),
+        };
+        replay_file(
+            SimKind::Core,
+            self.sim.seed(),
+            self.sim.config().hash(),
+            account,
+            summary,
+            &self.trace,
+        )
     }
+}
 
-    /// Player rect in pixels: [x, y, w, h].
-    pub fn player(&self) -> Vec<i32> {
-        let (x, y, w, h) = self.sim.player_px();
-        vec![x, y, w, h]
+impl RunHandle for DashSim {
+    fn with_run<R>(&self, f: impl FnOnce(&Sim) -> R) -> R {
+        f(&self.sim)
     }
 
-    /// Flat entity records: [entity_type, kind, x, y, w, h] per entity.
-    /// entity_type: 0 obstacle, 1 item, 2 fireball.
-    pub fn snapshot(&self) -> Vec<i32> {
-        let mut buf = [0i32; Sim::SNAPSHOT_CAP];
-        let n = self.sim.snapshot(&mut buf);
-        buf[..n].to_vec()
+    fn with_run_mut<R>(&mut self, f: impl FnOnce(&mut Sim) -> R) -> R {
+        f(&mut self.sim)
     }
+}
 
-    /// The recorded input trace (one byte per tick), for proving.
+run_api!(DashSim);
+
+/// A live ranked `dash_core` run racing up to `MAX_GHOSTS` recorded traces
+/// of the same seed (see `dash_core::ghost`). The live run has the same
+/// JS-facing API as [`DashSim`]; [`DashGhostRace::ghosts`] gives the
+/// ghosts to draw over it.
+#[wasm_bindgen]
+pub struct DashGhostRace {
+    race: GhostRace<Vec<u8>>,
+    trace: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl DashGhostRace {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> DashGhostRace {
+        DashGhostRace {
+            race: GhostRace::new(seed),
+            trace: Vec::new(),
+        }
+    }
+
+    /// Adds a ghost replaying `trace` (one byte per tick, as from
+    /// [`DashSim::trace`]) and returns its slot.
+    pub fn add_ghost(&mut self, trace: Vec<u8>) -> Result<usize, JsError> {
+        self.race
+            .add_ghost(trace)
+            .ok_or_else(|| JsError::new("all ghost slots are taken"))
+    }
+
+    /// Adds a ghost from a `.dario` replay file of a ranked run on this
+    /// seed and returns its slot.
+    pub fn add_ghost_replay(&mut self, file: &[u8]) -> Result<usize, JsError> {
+        let replay =
+            Replay::decode(file).map_err(|e| JsError::new(&format!("invalid replay: {e:?}")))?;
+        if replay.kind != SimKind::Core || replay.config_hash != SimConfig::NORMAL.hash() {
+            return Err(JsError::new("replay is not a ranked dash_core run"));
+        }
+        if replay.seed != self.race.seed() {
+            return Err(JsError::new("replay is for another seed"));
+        }
+        self.add_ghost(replay.trace)
+    }
+
+    /// Removes the ghost in `slot`; returns whether there was one.
+    pub fn remove_ghost(&mut self, slot: usize) -> bool {
+        self.race.remove_ghost(slot).is_some()
+    }
+
+    /// Advances the live run one 60Hz tick, recording `input`, and every
+    /// ghost by its own next input. Ghosts keep running once the live run
+    /// is over.
+    pub fn tick(&mut self, input: u8) {
+        let live = self.race.live();
+        let input = if live.over() || self.trace.len() >= dash_core::MAX_TICKS as usize {
+            None
+        } else {
+            Some(input & dash_core::INPUT_MASK)
+        };
+        if let Some(input) = input {
+            self.trace.push(input);
+        }
+        self.race.tick(input.unwrap_or(0));
+    }
+
+    /// Flat ghost records: [slot, x, y, w, h, form, alive] per ghost, in
+    /// player coordinates (`x` is offset by how far the ghost is ahead of
+    /// the live run; `alive` is 0 or 1).
+    pub fn ghosts(&self) -> Vec<i32> {
+        self.race
+            .ghost_views()
+            .flat_map(|g| {
+                [
+                    g.id as i32,
+                    g.x,
+                    g.y,
+                    g.w,
+                    g.h,
+                    g.form as i32,
+                    g.alive as i32,
+                ]
+            })
+            .collect()
+    }
+
+    /// The live run's place by distance, 1 for the lead.
+    pub fn place(&self) -> usize {
+        self.race.place()
+    }
+
+    /// The live run's recorded input trace, e.g. to race it later.
     pub fn trace(&self) -> Vec<u8> {
         self.trace.clone()
     }
 }
 
+impl RunHandle for DashGhostRace {
+    fn with_run<R>(&self, f: impl FnOnce(&Sim) -> R) -> R {
+        f(self.race.live())
+    }
+
+    fn with_run_mut<R>(&mut self, f: impl FnOnce(&mut Sim) -> R) -> R {
+        f(self.race.live_mut())
+    }
+}
+
+run_api!(DashGhostRace);
+
+/// Local split-screen match: two live ranked `dash_core` runs on one seed
+/// (see `dash_core::versus`). Each player's run is read through the
+/// [`DashMatchRun`] handle from [`DashMatch::run`].
+#[wasm_bindgen]
+pub struct DashMatch {
+    state: Rc<RefCell<MatchState>>,
+}
+
+struct MatchState {
+    game: Match,
+    record: Vec<u8>,
+    traces: [Vec<u8>; PLAYERS],
+}
+
+#[wasm_bindgen]
+impl DashMatch {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> DashMatch {
+        DashMatch {
+            state: Rc::new(RefCell::new(MatchState {
+                game: Match::new(seed),
+                record: Vec::new(),
+                traces: [Vec::new(), Vec::new()],
+            })),
+        }
+    }
+
+    /// Advances both players one 60Hz tick. `input` holds two bytes, each
+    /// laid out as [`DashSim::tick`]'s: player 0 in the low byte, player 1
+    /// in the high one.
+    pub fn tick(&mut self, input: u16) {
+        let state = &mut *self.state.borrow_mut();
+        if state.game.over() || state.game.ticks() >= dash_core::MAX_TICKS {
+            return;
+        }
+        let inputs = input.to_le_bytes().map(|b| b & dash_core::INPUT_MASK);
+        state.record.extend_from_slice(&inputs);
+        for (p, trace) in state.traces.iter_mut().enumerate() {
+            if !state.game.player(p).over() {
+                trace.push(inputs[p]);
+            }
+        }
+        state.game.tick(inputs);
+    }
+
+    /// Whether both runs are over.
+    pub fn over(&self) -> bool {
+        self.state.borrow().game.over()
+    }
+
+    /// The winning player once both runs are over; none while the match is
+    /// on or on a tie.
+    pub fn winner(&self) -> Option<usize> {
+        self.state.borrow().game.winner()
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.state.borrow().game.ticks()
+    }
+
+    /// The match record: both traces interleaved, two bytes per tick
+    /// (player 0 first), for `dash_core::versus::replay_match`.
+    pub fn record(&self) -> Vec<u8> {
+        self.state.borrow().record.clone()
+    }
+
+    /// A handle on `player`'s run, 0 or 1, that follows the match as it
+    /// ticks.
+    pub fn run(&self, player: usize) -> Result<DashMatchRun, JsError> {
+        if player >= PLAYERS {
+            return Err(JsError::new("a match has players 0 and 1"));
+        }
+        Ok(DashMatchRun {
+            state: Rc::clone(&self.state),
+            player,
+        })
+    }
+}
+
+/// One player's run in a [`DashMatch`], with the same per-run API as
+/// [`DashSim`].
+#[wasm_bindgen]
+pub struct DashMatchRun {
+    state: Rc<RefCell<MatchState>>,
+    player: usize,
+}
+
+#[wasm_bindgen]
+impl DashMatchRun {
+    /// The player's own input trace (one byte per tick), for proving.
+    pub fn trace(&self) -> Vec<u8> {
+        self.state.borrow().traces[self.player].clone()
+    }
+
+    /// The player's run as a `.dario` replay file; see
+    /// [`DashSim::replay_file`].
+    pub fn replay_file(&self, account: &[u8]) -> Result<Vec<u8>, JsError> {
+        let state = self.state.borrow();
+        let sim = state.game.player(self.player);
+        let summary = RunSummary {
+            score: sim.score(),
+            ticks: sim.ticks(),
+            over: sim.over(),
+        };
+        replay_file(
+            SimKind::Core,
+            sim.seed(),
+            sim.config().hash(),
+            account,
+            summary,
+            &state.traces[self.player],
+        )
+    }
+}
+
+impl RunHandle for DashMatchRun {
+    fn with_run<R>(&self, f: impl FnOnce(&Sim) -> R) -> R {
+        f(self.state.borrow().game.player(self.player))
+    }
+
+    fn with_run_mut<R>(&mut self, f: impl FnOnce(&mut Sim) -> R) -> R {
+        f(self.state.borrow_mut().game.player_mut(self.player))
+    }
+}
+
+run_api!(DashMatchRun);
+
 /// wasm wrapper around the 30 Hz browser-provable `dash_zk` sim.
 ///
 /// Same JS-facing API as [`DashSim`], plus [`ZkDashSim::input_json`], which
@@ -99,6 +701,7 @@ impl DashSim {
 pub struct ZkDashSim {
     sim: ZkSim,
     trace: Vec<u8>,
+    view: Vec<i32>,
 }
 
 #[wasm_bindgen]
@@ -108,19 +711,97 @@ impl ZkDashSim {
         ZkDashSim {
             sim: ZkSim::new(seed),
             trace: Vec::new(),
+            view: Vec::new(),
         }
     }
 
-    /// Advances one 30Hz tick, recording `input` (bit0 jump, bit1 fire).
+    /// Unranked variant whose power-ups expire after `expire_ticks` ticks.
+    /// Such runs cannot be proven; [`ZkDashSim::input_json`] rejects them.
+    pub fn with_form_expiry(seed: u64, expire_ticks: u32) -> ZkDashSim {
+        ZkDashSim {
+            sim: ZkSim::with_form_expiry(seed, expire_ticks),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant with a one-slot reserve deployed by bit2. Such
+    /// runs cannot be proven either.
+    pub fn with_reserve(seed: u64) -> ZkDashSim {
+        ZkDashSim {
+            sim: ZkSim::with_reserve(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant with coin arcs; the circuit has no coins yet.
+    pub fn with_coins(seed: u64) -> ZkDashSim {
+        ZkDashSim {
+            sim: ZkSim::with_coins(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Unranked variant on the chunked schedule; the contract only
+    /// recomputes the classic one.
+    pub fn with_chunks(seed: u64) -> ZkDashSim {
+        ZkDashSim {
+            sim: ZkSim::with_chunks(seed),
+            trace: Vec::new(),
+            view: Vec::new(),
+        }
+    }
+
+    /// Advances one 30Hz tick, recording `input` (bit0 jump, bit1 fire,
+    /// bit2 use reserve).
     pub fn tick(&mut self, input: u8) {
         if self.sim.over() || self.sim.ticks() >= dash_zk::MAX_TICKS {
             return;
         }
-        let input = input & (dash_zk::INPUT_JUMP | dash_zk::INPUT_FIRE);
+        let input = input & dash_zk::INPUT_MASK;
         self.trace.push(input);
         self.sim.tick(input);
     }
 
+    /// Like [`Self::tick`], but returns a capacity code on the tick one
+    /// first runs out and starts silently changing the run (ignored jumps,
+    /// items or fireballs passing through, missing obstacles): 0 jumps,
+    /// 1 pickups, 2 kills, 3 form events, 4 expiries, 5 deploys, then the
+    /// schedule lists 6 ground, 7 bats, 8 items, 9 spikes, 10 platforms,
+    /// 11 coins. The run stays provable either way.
+    pub fn try_tick(&mut self, input: u8) -> Option<u32> {
+        if self.sim.over() || self.sim.ticks() >= dash_zk::MAX_TICKS {
+            return None;
+        }
+        let input = input & dash_zk::INPUT_MASK;
+        self.trace.push(input);
+        self.sim.try_tick(input).err().map(|e| e.capacity() as u32)
+    }
+
+    /// Room left in the event logs, to warn before one runs out: [jumps,
+    /// pickups, kills, form events, expiries, deploys].
+    pub fn remaining_capacity(&self) -> Vec<u32> {
+        let r = self.sim.remaining_capacity();
+        [
+            r.jumps,
+            r.pickups,
+            r.kills,
+            r.form_events,
+            r.expiries,
+            r.deploys,
+        ]
+        .map(|n| n as u32)
+        .to_vec()
+    }
+
+    /// Codes (as in [`Self::try_tick`]) of every capacity that ran out so
+    /// far.
+    pub fn exhausted(&self) -> Vec<u32> {
+        self.sim.exhausted().map(|c| c as u32).collect()
+    }
+
     pub fn over(&self) -> bool {
         self.sim.over()
     }
@@ -129,15 +810,47 @@ impl ZkDashSim {
         self.sim.score()
     }
 
+    /// Where the score came from, laid out like
+    /// [`DashSim::score_breakdown`] (bosses is always 0): the first five
+    /// minus capped_by, the points `MAX_RANKED_SCORE` clamped off, give the
+    /// score.
+    pub fn score_breakdown(&self) -> Vec<u64> {
+        let b = self.sim.score_breakdown();
+        vec![b.distance, b.pickups, b.kills, b.coins, 0, b.capped_by]
+    }
+
     pub fn ticks(&self) -> u32 {
         self.sim.ticks()
     }
 
+    /// Rolling state hash after the latest tick, for desync bisection
+    /// against a native replay of [`Self::trace`]; 0 until
+    /// [`Self::hash_states`].
+    pub fn state_hash(&self) -> u64 {
+        self.sim.state_hash()
+    }
+
+    /// Turns on [`Self::state_hash`]. Call before the first tick to
+    /// compare against a native replay.
+    pub fn hash_states(&mut self) {
+        self.sim.hash_states();
+    }
+
     /// Current FSM form (DarioState as u32).
     pub fn form(&self) -> u32 {
         self.sim.form()
     }
 
+    /// Ticks until the current power-up expires, if it expires at all.
+    pub fn form_ticks_left(&self) -> Option<u32> {
+        self.sim.form_ticks_left()
+    }
+
+    /// Reserve item kind (0 espresso, 1 chili, 2 cape), if one is stored.
+    pub fn reserve(&self) -> Option<u32> {
+        self.sim.reserve()
+    }
+
     pub fn pickups(&self) -> u32 {
         self.sim.pickups_total()
     }
@@ -146,6 +859,10 @@ impl ZkDashSim {
         self.sim.kills_total()
     }
 
+    pub fn coins(&self) -> u32 {
+        self.sim.coins_total()
+    }
+
     pub fn distance_px(&self) -> u64 {
         self.sim.distance_px()
     }
@@ -165,18 +882,59 @@ impl ZkDashSim {
     }
 
     /// Flat entity records: [entity_type, kind, x, y, w, h] per entity.
-    /// entity_type: 0 obstacle, 1 item, 2 fireball.
+    /// entity_type: 0 obstacle, 1 item, 2 fireball, 3 coin.
     pub fn snapshot(&self) -> Vec<i32> {
-        let mut buf = [0i32; 80 * 6];
+        let mut buf = [0i32; ZkSim::SNAPSHOT_CAP];
         let n = self.sim.snapshot(&mut buf);
         buf[..n].to_vec()
     }
 
+    /// Refreshes the zero-copy entity view and returns the entity count.
+    /// Read it as `new Int32Array(memory.buffer, sim.view_ptr(), 7 * n)`:
+    /// `[id, entity_type, kind, x, y, w, h]` per entity, where ids are
+    /// stable across frames. Re-create the array after wasm memory grows.
+    pub fn refresh_view(&mut self) -> usize {
+        fill_view(&mut self.view, ZkSim::MAX_ENTITIES, self.sim.entities())
+    }
+
+    /// Address of the entity view in wasm memory; valid after
+    /// [`Self::refresh_view`] until the sim is freed.
+    pub fn view_ptr(&self) -> *const i32 {
+        self.view.as_ptr()
+    }
+
     /// The recorded input trace (one byte per tick).
     pub fn trace(&self) -> Vec<u8> {
         self.trace.clone()
     }
 
+    /// [`Self::trace`] run-length packed (`dario_replay::trace`); usually
+    /// a few hundred bytes for a full run.
+    pub fn trace_compressed(&self) -> Vec<u8> {
+        let mut packed = Vec::new();
+        dario_replay::trace::encode(&self.trace, &mut packed).expect("trace bytes are masked");
+        packed
+    }
+
+    /// The run as a `.dario` replay file for `export_input`. `account` is
+    /// the 96-byte Moonlight account, or empty to leave it unbound. The
+    /// config hash is recorded, so unranked runs export too.
+    pub fn replay_file(&self, account: &[u8]) -> Result<Vec<u8>, JsError> {
+        let summary = RunSummary {
+            score: self.sim.score(),
+            ticks: self.sim.ticks(),
+            over: self.sim.over(),
+        };
+        replay_file(
+            SimKind::Zk,
+            self.sim.seed,
+            self.sim.config_hash(),
+            account,
+            summary,
+            &self.trace,
+        )
+    }
+
     /// The full snarkjs circuit input (publics + private witness) for the
     /// finished run. `acct_hex` is the caller's 96-byte Moonlight account
     /// (192 hex chars), or empty to leave the account unbound (zeros).
@@ -197,6 +955,8 @@ impl ZkDashSim {
                 *limb = u128::from_le_bytes(chunk);
             }
         }
-        Ok(dash_zk::input_json::build_input_json(&self.sim, &acct))
+        dash_zk::input_json::build_input_json(&self.sim, &acct).map_err(|_| {
+            JsError::new("unranked runs (form expiry, reserve, coins or chunks) cannot be proven")
+        })
     }
 }
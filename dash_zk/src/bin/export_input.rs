@@ -3,11 +3,15 @@
 //!
 //! Usage: export_input <seed> <out_input.json> [trace_file|-] [acct_hex_96B]
 //!
-//! The trace file, if given, is raw bytes, one input byte per tick
-//! (bit0 jump, bit1 fire). Without it (or with `-`) a built-in autopilot
-//! plays. The optional account hex (192 chars) is bound into the proof as
-//! 6 little-endian u128 limbs.
+//! The seed is a number or `daily:<day>` for that day's daily challenge.
+//!
+//! The trace file, if given, is either a `.dario` replay of a `dash_zk` run
+//! or raw bytes, one input byte per tick (bit0 jump, bit1 fire). Without it
+//! (or with `-`) a built-in autopilot plays. The optional account hex (192
+//! chars) is bound into the proof as 6 little-endian u128 limbs; it
+//! defaults to the replay's account, if any.
 
+use dario_replay::{is_replay, Replay, SimKind};
 use dash_zk::input_json::build_input_json;
 use dash_zk::*;
 
@@ -60,9 +64,22 @@ fn main() {
         eprintln!("usage: export_input <seed> <out_input.json> [trace_file|-] [acct_hex_96B]");
         std::process::exit(1);
     }
-    let seed: u64 = args[1].parse().expect("seed");
+    let seed = dash_zk::daily::parse_seed(&args[1]).expect("seed");
+    let mut replay_account = None;
     let sim = if args.len() > 3 && args[3] != "-" {
-        let trace = std::fs::read(&args[3]).expect("trace file");
+        let mut trace = std::fs::read(&args[3]).expect("trace file");
+        if is_replay(&trace) {
+            let replay = Replay::decode(&trace).expect("replay file");
+            assert_eq!(replay.kind, SimKind::Zk, "replay is not a dash_zk run");
+            assert_eq!(replay.seed, seed, "replay was recorded with another seed");
+            assert_eq!(
+                replay.config_hash,
+                ZkSim::new(seed).config_hash(),
+                "replay was recorded under unranked rules"
+            );
+            replay_account = replay.account;
+            trace = replay.trace;
+        }
         replay_run(seed, &trace)
     } else {
         autopilot_run(seed)
@@ -78,7 +95,12 @@ fn main() {
                 u128::from_le_bytes(bytes[i * 16..(i + 1) * 16].try_into().unwrap())
             })
         }
-        None => [0; 6],
+        None => match replay_account {
+            Some(bytes) => core::array::from_fn(|i| {
+                u128::from_le_bytes(bytes[i * 16..(i + 1) * 16].try_into().unwrap())
+            }),
+            None => [0; 6],
+        },
     };
 
     eprintln!(
@@ -93,6 +115,7 @@ fn main() {
         sim.damage_count,
     );
 
-    std::fs::write(&args[2], build_input_json(&sim, &acct)).expect("write output");
+    let json = build_input_json(&sim, &acct).expect("only ranked runs can be proven");
+    std::fs::write(&args[2], json).expect("write output");
     eprintln!("wrote {}", args[2]);
 }
@@ -0,0 +1,102 @@
+//! Daily challenge seeds shared by the web app, the tools and the contract.
+//!
+//! Day `d` is the UTC day `d` days after the Unix epoch ([`day_of`]). Its
+//! seed is derived from the day and a domain string, so separate events
+//! (or a new season) can run their own daily series off the same days;
+//! [`DAILY_DOMAIN`] is the canonical one.
+//!
+//! A daily seed keeps the day in its low 32 bits and 21 hash bits above
+//! them, never all zero. So daily seeds stay below 2^53, exact as JS
+//! numbers like every seed the web app sends to the contract; they never
+//! collide with the 32-bit random seeds of free play; and [`daily_day`]
+//! recovers the day from a seed without searching.
+
+/// The canonical daily series.
+pub const DAILY_DOMAIN: &str = "dario-dash/daily/v1";
+
+pub const SECS_PER_DAY: u64 = 86_400;
+
+/// Hash bits above the day, keeping seeds below 2^53.
+const HASH_BITS: u32 = 21;
+
+/// The day index of a Unix timestamp in seconds.
+pub fn day_of(unix_secs: u64) -> u32 {
+    (unix_secs / SECS_PER_DAY) as u32
+}
+
+/// The seed of `day` in the series named `domain`.
+pub fn daily_seed(day: u32, domain: &str) -> u64 {
+    // FNV-1a over the domain, then a splitmix64 finalizer over the day.
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in domain.as_bytes() {
+        h = (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+    }
+    let mut z = h ^ u64::from(day).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    let high = 1 + z % ((1 << HASH_BITS) - 1);
+    high << 32 | u64::from(day)
+}
+
+/// The day whose seed in `domain` is `seed`, if any.
+pub fn daily_day(seed: u64, domain: &str) -> Option<u32> {
+    let day = seed as u32;
+    (daily_seed(day, domain) == seed).then_some(day)
+}
+
+/// Parses a seed argument for the tools: a number, or `daily:<day>` for
+/// that day's canonical daily seed.
+pub fn parse_seed(arg: &str) -> Option<u64> {
+    match arg.strip_prefix("daily:") {
+        Some(day) => Some(daily_seed(day.parse().ok()?, DAILY_DOMAIN)),
+        None => arg.parse().ok(),
+    }
+}
+
+/// Entries kept by a daily leaderboard.
+pub const LEADERBOARD_LEN: usize = 10;
+
+/// Whether a daily run of `score` in `ticks` beats `best` (score, ticks):
+/// a higher score, or the same score in fewer ticks.
+pub fn beats(score: u64, ticks: u32, best: (u64, u32)) -> bool {
+    score > best.0 || (score == best.0 && ticks < best.1)
+}
+
+/// Sorts daily (entrant, score, ticks) entries best first, as [`beats`]
+/// ranks them, keeping entrants' order on ties, and keeps the first
+/// [`LEADERBOARD_LEN`].
+pub fn rank<A>(entries: &mut alloc::vec::Vec<(A, u64, u32)>) {
+    entries.sort_by_key(|&(_, score, ticks)| (core::cmp::Reverse(score), ticks));
+    entries.truncate(LEADERBOARD_LEN);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_seeds_are_js_safe_and_invertible() {
+        // 2026-10-19 00:00 UTC.
+        let today = day_of(1_792_368_000);
+        assert_eq!(today, 20_745);
+        let mut prev = 0;
+        for day in [0, 1, today, today + 1, u32::MAX] {
+            let seed = daily_seed(day, DAILY_DOMAIN);
+            assert!(seed > u64::from(u32::MAX) && seed < 1 << 53);
+            assert_ne!(seed, prev);
+            assert_eq!(daily_day(seed, DAILY_DOMAIN), Some(day));
+            assert_eq!(daily_day(seed, "dario-dash/daily/v2"), None);
+            assert_ne!(daily_seed(day, "dario-dash/daily/v2"), seed);
+            prev = seed;
+        }
+        // Free-play seeds are never daily.
+        assert_eq!(daily_day(u64::from(today), DAILY_DOMAIN), None);
+        assert_eq!(
+            parse_seed("daily:20745"),
+            Some(daily_seed(today, DAILY_DOMAIN))
+        );
+        assert_eq!(parse_seed("42"), Some(42));
+        assert_eq!(parse_seed("daily:x"), None);
+    }
+}
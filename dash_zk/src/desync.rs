@@ -0,0 +1,94 @@
+//! Desync detection for [`ZkSim`]: [`ZkSim::hash_states`] turns on the
+//! rolling per-tick state hash and the shared [`dario_desync`] recorder
+//! bisects two runs to the first tick where they differ.
+
+use crate::ZkSim;
+pub use dario_desync::{first_divergence, mix, record_checkpoints, Checkpoints, HashedSim};
+
+impl HashedSim for ZkSim {
+    fn tick(&mut self, input: u8) {
+        ZkSim::tick(self, input)
+    }
+
+    fn ticks(&self) -> u32 {
+        ZkSim::ticks(self)
+    }
+
+    fn over(&self) -> bool {
+        ZkSim::over(self)
+    }
+
+    fn state_hash(&self) -> u64 {
+        ZkSim::state_hash(self)
+    }
+
+    fn hash_states(&mut self) {
+        ZkSim::hash_states(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{INPUT_FIRE, INPUT_JUMP};
+
+    fn trace() -> [u8; 3000] {
+        let mut t = [0u8; 3000];
+        for (i, b) in t.iter_mut().enumerate() {
+            if i % 40 < 10 {
+                *b |= INPUT_JUMP;
+            }
+            if i % 9 == 0 {
+                *b |= INPUT_FIRE;
+            }
+        }
+        t
+    }
+
+    #[test]
+    fn first_divergence_bisects_to_the_tampered_tick() {
+        let trace = trace();
+        let mut a = [0u64; 3000];
+        let n = record_checkpoints(ZkSim::new(42), &trace, 1, &mut a);
+        let mut b = [0u64; 3000];
+        assert_eq!(record_checkpoints(ZkSim::new(42), &trace, 1, &mut b), n);
+        assert_eq!(first_divergence(1, &a[..n], &b[..n]), None);
+
+        let mut tampered = trace;
+        tampered[39] ^= INPUT_JUMP;
+        let m = record_checkpoints(ZkSim::new(42), &tampered, 1, &mut b);
+        assert_eq!(first_divergence(1, &a[..n], &b[..m]), Some(40));
+
+        let mut coarse_a = [0u64; 64];
+        let mut coarse_b = [0u64; 64];
+        let na = record_checkpoints(ZkSim::new(42), &trace, 16, &mut coarse_a);
+        let nb = record_checkpoints(ZkSim::new(42), &tampered, 16, &mut coarse_b);
+        assert_eq!(
+            first_divergence(16, &coarse_a[..na], &coarse_b[..nb]),
+            Some(48)
+        );
+    }
+
+    #[test]
+    fn different_seeds_diverge_on_the_first_tick() {
+        let mut a = [0u64; 4];
+        let mut b = [0u64; 4];
+        record_checkpoints(ZkSim::new(1), &[0; 4], 1, &mut a);
+        record_checkpoints(ZkSim::new(2), &[0; 4], 1, &mut b);
+        assert_eq!(first_divergence(1, &a, &b), Some(1));
+    }
+
+    #[test]
+    fn state_hashing_is_opt_in() {
+        let mut plain = ZkSim::new(7);
+        let mut hashed = ZkSim::new(7);
+        hashed.hash_states();
+        for _ in 0..30 {
+            plain.tick(INPUT_JUMP);
+            hashed.tick(INPUT_JUMP);
+        }
+        assert_eq!(plain.state_hash(), 0);
+        assert_ne!(hashed.state_hash(), 0);
+        assert_eq!(plain.score(), hashed.score());
+    }
+}
@@ -0,0 +1,211 @@
+//! Diagnostic replays: how and why a run ended.
+//!
+//! [`replay`](crate::replay) returns a bare `(score, ticks, over)`.
+//! [`diagnose`] replays the same trace and also reports the [`Ending`]
+//! (for a death, the scheduled obstacle and both hitboxes), every form
+//! change, the score by source and the capacities that ran out (see
+//! [`Capacity`]). A full log or schedule list changes the run without any
+//! visible sign, so that is the first thing to check when a trace does not
+//! prove. The [`Diagnosis`] has the same shape as `dash_core`'s; only the
+//! [`Death`] details differ.
+
+use crate::{
+    bat_hitbox, ground_hitbox, Capacity, ScoreBreakdown, ZkSim, MAX_RANKED_SCORE, MAX_TICKS,
+    PLAYER_BOX_BOT_FP, PLAYER_BOX_TOP_FP, PLAYER_LEFT100, PLAYER_RIGHT100,
+};
+use dario_diagnose::{FirstTicks, FormLog};
+pub use dario_diagnose::{FormChange, MAX_FORM_CHANGES};
+use dario_fsm::DarioState;
+
+/// A hitbox as the sim checks it: horizontal edges in fp100, vertical in
+/// fp.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hitbox {
+    pub left100: i64,
+    pub right100: i64,
+    pub top_fp: i32,
+    pub bot_fp: i32,
+}
+
+impl Hitbox {
+    pub fn overlaps(&self, other: &Hitbox) -> bool {
+        self.left100 < other.right100
+            && self.right100 > other.left100
+            && self.top_fp < other.bot_fp
+            && self.bot_fp > other.top_fp
+    }
+}
+
+impl From<(i64, i64, i32, i32)> for Hitbox {
+    fn from((left100, right100, top_fp, bot_fp): (i64, i64, i32, i32)) -> Self {
+        Hitbox {
+            left100,
+            right100,
+            top_fp,
+            bot_fp,
+        }
+    }
+}
+
+/// What dealt the final hit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Killer {
+    /// Ground obstacle `idx` of the schedule (`KIND_*`).
+    Ground { idx: u32, kind: i32 },
+    /// Bat `idx` of the schedule.
+    Bat { idx: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Death {
+    pub tick: u32,
+    pub killer: Killer,
+    /// Dario's and the killer's hitboxes on the fatal tick.
+    pub player: Hitbox,
+    pub hitbox: Hitbox,
+}
+
+/// How the run ended; [`Ending::ScoreCap`] once [`MAX_RANKED_SCORE`] is
+/// reached.
+pub type Ending = dario_diagnose::Ending<Death>;
+
+#[derive(Clone, Debug)]
+pub struct Diagnosis {
+    pub score: u64,
+    pub ticks: u32,
+    pub over: bool,
+    pub ending: Ending,
+    pub breakdown: ScoreBreakdown,
+    forms: FormLog,
+    exhausted: FirstTicks<{ Capacity::ALL.len() }>,
+}
+
+impl Diagnosis {
+    /// Form changes in order, at most [`MAX_FORM_CHANGES`].
+    pub fn forms(&self) -> &[FormChange] {
+        self.forms.as_slice()
+    }
+
+    /// Each capacity that ran out, with the first tick it changed the run,
+    /// in [`Capacity::ALL`] order.
+    pub fn exhausted(&self) -> impl Iterator<Item = (Capacity, u32)> + '_ {
+        self.exhausted.iter(Capacity::ALL)
+    }
+}
+
+/// Replays `trace` from `seed` under the ranked rules, like
+/// [`replay`](crate::replay), and explains the result.
+pub fn diagnose(seed: u64, trace: &[u8]) -> Diagnosis {
+    diagnose_sim(ZkSim::new(seed), trace)
+}
+
+/// Replays `trace` on `sim` (any variant) and explains the result.
+pub fn diagnose_sim(mut sim: ZkSim, trace: &[u8]) -> Diagnosis {
+    let mut forms = FormLog::new();
+    let mut exhausted = FirstTicks::new();
+    for &input in trace {
+        if sim.over() {
+            break;
+        }
+        let from = sim.form;
+        sim.tick(input);
+        if sim.form != from {
+            forms.push(FormChange {
+                tick: sim.ticks(),
+                from,
+                to: sim.form,
+            });
+        }
+        for c in sim.exhausted() {
+            exhausted.note(c as usize, sim.ticks());
+        }
+    }
+    let ending = if sim.form == DarioState::GameOver {
+        death(&sim).map_or(Ending::Unfinished, Ending::Died)
+    } else if sim.score() >= MAX_RANKED_SCORE {
+        Ending::ScoreCap
+    } else if sim.ticks() >= MAX_TICKS {
+        Ending::TimeUp
+    } else {
+        Ending::Unfinished
+    };
+    Diagnosis {
+        score: sim.score(),
+        ticks: sim.ticks(),
+        over: sim.over(),
+        ending,
+        breakdown: sim.score_breakdown(),
+        forms,
+        exhausted,
+    }
+}
+
+/// The last damaging collision, which is the fatal one.
+fn death(sim: &ZkSim) -> Option<Death> {
+    let dmg = sim.damages[..sim.damage_count]
+        .iter()
+        .rev()
+        .find(|d| !d.invuln_touch)?;
+    let idx = dmg.idx;
+    let (killer, hitbox) = if dmg.class == 0 {
+        let g = &sim.sched.ground[idx as usize];
+        let kind = g.kind;
+        (Killer::Ground { idx, kind }, ground_hitbox(g, dmg.tick))
+    } else {
+        let b = &sim.sched.bats[idx as usize];
+        (Killer::Bat { idx }, bat_hitbox(b, dmg.tick))
+    };
+    let py = sim.player_y_fp(dmg.tick);
+    Some(Death {
+        tick: dmg.tick,
+        killer,
+        player: Hitbox {
+            left100: PLAYER_LEFT100,
+            right100: PLAYER_RIGHT100,
+            top_fp: py - PLAYER_BOX_TOP_FP,
+            bot_fp: py - PLAYER_BOX_BOT_FP,
+        },
+        hitbox: hitbox.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{jump_landing, replay, INPUT_JUMP, JUMP_V, MAX_JUMPS};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn idle_deaths_name_the_obstacle_and_add_up() {
+        for seed in [1, 42, 99] {
+            let trace = [0u8; 1000];
+            let d = diagnose(seed, &trace);
+            assert_eq!((d.score, d.ticks, d.over), replay(seed, &trace));
+            assert_eq!(d.breakdown.total(), d.score);
+            let Ending::Died(death) = d.ending else {
+                panic!("seed {seed}: idle run should die");
+            };
+            assert_eq!(death.tick, d.ticks);
+            assert!(death.player.overlaps(&death.hitbox));
+            let last = d.forms().last().unwrap();
+            assert_eq!((last.tick, last.to), (death.tick, DarioState::GameOver));
+            assert_eq!(d.exhausted().count(), 0);
+        }
+    }
+
+    #[test]
+    fn full_jump_logs_are_reported() {
+        // Nothing to hit and room for one more jump.
+        let mut sim = ZkSim::new(5);
+        sim.sched.ground_count = 0;
+        sim.sched.bat_count = 0;
+        sim.jump_count = MAX_JUMPS - 1;
+        let trace: Vec<u8> = (0..200).map(|t| (t % 2) as u8 * INPUT_JUMP).collect();
+        let d = diagnose_sim(sim, &trace);
+        assert_eq!((d.ending, d.ticks), (Ending::Unfinished, 200));
+        assert_eq!(d.breakdown.total(), d.score);
+        // The first press jumps; the first one after landing is ignored.
+        let land = jump_landing(JUMP_V, false);
+        assert!(d.exhausted().eq([(Capacity::Jumps, 2 + land + 1)]));
+    }
+}
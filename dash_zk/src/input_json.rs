@@ -165,10 +165,20 @@ fn onehot(n: usize, idx: Option<usize>) -> Vec<i64> {
     v
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputJsonError {
+    /// The run is not [`ZkSim::ranked`]: the circuit has no form expiry,
+    /// reserve, coins or chunked schedule.
+    Unranked,
+}
+
 /// Serializes the full circuit input (publics + private witness) for a
 /// finished run. `acct` is the caller's 96-byte account as 6 little-endian
 /// u128 limbs; use zeros when no account is bound yet.
-pub fn build_input_json(sim: &ZkSim, acct: &[u128; 6]) -> String {
+pub fn build_input_json(sim: &ZkSim, acct: &[u128; 6]) -> Result<String, InputJsonError> {
+    if !sim.ranked() {
+        return Err(InputJsonError::Unranked);
+    }
     let sched = sim.schedule();
     let w = RunWitness::extract(sim);
     let entries = build_entries(sim);
@@ -366,7 +376,7 @@ pub fn build_input_json(sim: &ZkSim, acct: &[u128; 6]) -> String {
             .map(|i| {
                 if i < kn {
                     let k = kills[i];
-                    let g = if k.target_class == 0 {
+                    let g = if k.target_class == TARGET_GROUND {
                         k.target_idx as usize
                     } else {
                         NG + k.target_idx as usize
@@ -406,7 +416,7 @@ pub fn build_input_json(sim: &ZkSim, acct: &[u128; 6]) -> String {
     let mut ktr = vec![0i64; NK];
     for i in 0..kn {
         let k = kills[i];
-        if k.target_class == 1 {
+        if k.target_class == TARGET_BAT {
             let b = sched.bats[k.target_idx as usize];
             let (q, r) = tri_qr(b.phase0 + k.hit_tick - b.spawn_tick);
             ktq[i] = i64::from(q);
@@ -525,5 +535,26 @@ pub fn build_input_json(sim: &ZkSim, acct: &[u128; 6]) -> String {
     j.num("scoreQ", dt / denom);
     j.num("scoreR", dt % denom);
 
-    j.finish()
+    Ok(j.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_ranked_runs_have_circuit_inputs() {
+        let mut ranked = ZkSim::new(3);
+        let mut coins = ZkSim::with_coins(3);
+        for _ in 0..60 {
+            ranked.tick(0);
+            coins.tick(0);
+        }
+        let json = build_input_json(&ranked, &[0; 6]).unwrap();
+        assert!(json.starts_with('{') && json.contains("\"scoreQ\""));
+        assert_eq!(
+            build_input_json(&coins, &[0; 6]),
+            Err(InputJsonError::Unranked)
+        );
+    }
 }
@@ -24,9 +24,14 @@
 
 extern crate alloc;
 
-use dario_fsm::{transition, DarioState, Event};
+use dario_fsm::{is_powered, step, transition, DarioState, Event, Loadout, PowerUp};
+use dario_levels::{Chunker, Piece};
 
+pub mod daily;
+pub mod desync;
+pub mod diagnose;
 pub mod input_json;
+pub mod view;
 pub mod witness;
 
 pub const TICK_HZ: u32 = 30;
@@ -41,7 +46,9 @@ pub const GROUND_Y: i32 = 464;
 
 pub const INPUT_JUMP: u8 = 1;
 pub const INPUT_FIRE: u8 = 2;
-pub const INPUT_MASK: u8 = INPUT_JUMP | INPUT_FIRE;
+/// Deploys the reserve item (only with [`ZkSim::with_reserve`]).
+pub const INPUT_USE: u8 = 4;
+pub const INPUT_MASK: u8 = INPUT_JUMP | INPUT_FIRE | INPUT_USE;
 
 /// Ranked runs are capped at 2 minutes.
 pub const MAX_TICKS: u32 = 2 * 60 * TICK_HZ; // 3600
@@ -95,11 +102,23 @@ pub const ITEM_SIZE: i32 = 34;
 pub const KIND_BARREL: i32 = 0;
 pub const KIND_PIPE: i32 = 1;
 pub const KIND_BAT: i32 = 2;
+/// Ceiling spike and moving platform, mirroring `dash_core`'s hazards
+/// mode. Only [`Schedule::with_hazards`] schedules them; [`ZkSim`] and the
+/// circuit do not simulate them yet.
+pub const KIND_SPIKE: i32 = 3;
+pub const KIND_PLATFORM: i32 = 4;
 
 pub const ITEM_ESPRESSO: i32 = 0;
 pub const ITEM_CHILI: i32 = 1;
 pub const ITEM_CAPE: i32 = 2;
 
+/// Coins (unranked, [`ZkSim::with_coins`]): arcs of five, mirroring
+/// `dash_core`'s coin mode. Heights are px above the ground line.
+pub const COIN_SCORE: u64 = 10;
+pub const COIN_SIZE: i32 = 20;
+pub const COIN_ARC: [i32; 5] = [44, 96, 120, 96, 44];
+pub const COIN_SPACING: i32 = 44;
+
 /// Bat hover: triangle wave, period 36 ticks, step 280 fp per unit
 /// (peak amplitude ±18·280 fp ≈ ±19.7 px). No divisions.
 pub const BAT_PERIOD: u32 = 36;
@@ -107,15 +126,119 @@ pub const BAT_STEP: i32 = 280;
 pub const BAT_W: i32 = 40;
 pub const BAT_H: i32 = 32;
 
+pub const SPIKE_W: i32 = 36;
+/// Platform bob: triangle wave, period 60 ticks, ±16 px (`dash_core`'s
+/// 120-tick wave at 30 Hz).
+pub const PLATFORM_PERIOD: u32 = 60;
+pub const PLATFORM_W: i32 = 110;
+pub const PLATFORM_H: i32 = 14;
+
 // --- Schedule / witness capacity (mirrored by the circuit) ---
 pub const MAX_GROUND: usize = 128;
 pub const MAX_BATS: usize = 24;
 pub const MAX_SCHED_ITEMS: usize = 56;
+/// Hazard capacity (unranked schedules only; not yet in the circuit).
+pub const MAX_SPIKES: usize = 24;
+pub const MAX_PLATFORMS: usize = 24;
+/// Scheduled coins (unranked schedules only; not yet in the circuit).
+pub const MAX_SCHED_COINS: usize = 240;
 pub const MAX_JUMPS: usize = 160;
 pub const MAX_PICKUPS: usize = 56;
 pub const MAX_KILLS: usize = 32;
 pub const MAX_DAMAGES: usize = 8;
 pub const MAX_FORM_EVENTS: usize = MAX_PICKUPS + MAX_DAMAGES;
+/// Power-up expiries (unranked runs only; every expiry follows a pickup).
+pub const MAX_EXPIRIES: usize = MAX_PICKUPS;
+/// Reserve deploys (unranked runs only).
+pub const MAX_DEPLOYS: usize = MAX_PICKUPS;
+
+/// A schedule list or event log whose capacity the circuit fixes. A full
+/// schedule list drops later spawns; once an event log is full, the sim
+/// suppresses what it cannot record (a jump, a kill, a pickup, a
+/// collision, ...) instead of desyncing from the witness.
+/// [`ZkSim::exhausted`] reports which ones ran out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capacity {
+    /// [`MAX_JUMPS`]: jump presses on the ground are ignored.
+    Jumps,
+    /// [`MAX_PICKUPS`]: items pass through Dario.
+    Pickups,
+    /// [`MAX_KILLS`]: fireballs pass through obstacles.
+    Kills,
+    /// [`MAX_FORM_EVENTS`], shared by pickups and damage: both are
+    /// suppressed, collisions included.
+    FormEvents,
+    /// [`MAX_EXPIRIES`]: powered forms stop expiring.
+    Expiries,
+    /// [`MAX_DEPLOYS`]: the reserve cannot be deployed.
+    Deploys,
+    /// [`MAX_GROUND`]: ground obstacles were left out of the schedule.
+    Ground,
+    /// [`MAX_BATS`]: bats were left out of the schedule.
+    Bats,
+    /// [`MAX_SCHED_ITEMS`]: items were left out of the schedule.
+    Items,
+    /// [`MAX_SPIKES`], [`MAX_PLATFORMS`] and [`MAX_SCHED_COINS`]: unranked
+    /// spawns were left out of the schedule.
+    Spikes,
+    Platforms,
+    Coins,
+}
+
+impl Capacity {
+    pub const ALL: [Capacity; 12] = [
+        Capacity::Jumps,
+        Capacity::Pickups,
+        Capacity::Kills,
+        Capacity::FormEvents,
+        Capacity::Expiries,
+        Capacity::Deploys,
+        Capacity::Ground,
+        Capacity::Bats,
+        Capacity::Items,
+        Capacity::Spikes,
+        Capacity::Platforms,
+        Capacity::Coins,
+    ];
+
+    /// True for the [`Schedule`] lists, false for the event logs.
+    pub fn is_schedule(self) -> bool {
+        self as u8 >= Capacity::Ground as u8
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u8
+    }
+}
+
+/// Returned by [`ZkSim::try_tick`] on the tick a capacity first runs out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CapacityError {
+    /// The event log is full and an event was suppressed this tick.
+    LogFull(Capacity),
+    /// A spawn due this tick was left out of the full schedule list.
+    ScheduleFull(Capacity),
+}
+
+impl CapacityError {
+    pub fn capacity(self) -> Capacity {
+        match self {
+            CapacityError::LogFull(c) | CapacityError::ScheduleFull(c) => c,
+        }
+    }
+}
+
+/// Room left in each event log; see [`ZkSim::remaining_capacity`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RemainingCapacity {
+    pub jumps: usize,
+    pub pickups: usize,
+    pub kills: usize,
+    /// Shared by pickups and damage; usually the first to run out.
+    pub form_events: usize,
+    pub expiries: usize,
+    pub deploys: usize,
+}
 
 /// Total scrolled distance after `t` ticks, fp100. Exact closed form of
 /// `Σ_{u=1..t} min(BASE + ACCEL·u, MAX)`.
@@ -130,15 +253,60 @@ pub fn d100(t: u32) -> i64 {
     }
 }
 
+/// A score by source; [`ScoreBreakdown::total`] is the score.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    /// 0.02 points per scrolled pixel.
+    pub distance: u64,
+    /// 50 per item.
+    pub pickups: u64,
+    /// 25 per fireball kill.
+    pub kills: u64,
+    /// [`COIN_SCORE`] per coin, coin schedules only.
+    pub coins: u64,
+    /// Points the [`MAX_RANKED_SCORE`] cap removed.
+    pub capped_by: u64,
+}
+
+impl ScoreBreakdown {
+    /// The score before the cap.
+    pub fn raw(&self) -> u64 {
+        self.distance + self.pickups + self.kills + self.coins
+    }
+
+    pub fn total(&self) -> u64 {
+        self.raw() - self.capped_by
+    }
+}
+
+/// The score formula by source, capped at [`MAX_RANKED_SCORE`];
+/// [`score_with_coins`] is its total.
+pub fn score_breakdown(t: u32, pickups: u32, kills: u32, coins: u32) -> ScoreBreakdown {
+    let mut score = ScoreBreakdown {
+        distance: (d100(t) / (i64::from(FP) * 100 * 50)) as u64,
+        pickups: u64::from(pickups) * 50,
+        kills: u64::from(kills) * 25,
+        coins: u64::from(coins) * COIN_SCORE,
+        capped_by: 0,
+    };
+    score.capped_by = score.raw().saturating_sub(MAX_RANKED_SCORE);
+    score
+}
+
 pub fn raw_ranked_score(t: u32, pickups: u32, kills: u32) -> u64 {
-    let distance_score = (d100(t) / (i64::from(FP) * 100 * 50)) as u64;
-    distance_score + u64::from(pickups) * 50 + u64::from(kills) * 25
+    score_breakdown(t, pickups, kills, 0).raw()
 }
 
 pub fn ranked_score(t: u32, pickups: u32, kills: u32) -> u64 {
     raw_ranked_score(t, pickups, kills).min(MAX_RANKED_SCORE)
 }
 
+/// The score formula with coins, capped like [`ranked_score`]; equal to it
+/// when no coins were collected.
+pub fn score_with_coins(t: u32, pickups: u32, kills: u32, coins: u32) -> u64 {
+    score_breakdown(t, pickups, kills, coins).total()
+}
+
 /// Scroll speed at tick `t`, fp100/tick.
 pub fn s100(t: u32) -> i64 {
     let s = SPEED_BASE100 + SPEED_ACCEL100 * i64::from(t);
@@ -231,6 +399,21 @@ pub struct BatObs {
     pub phase0: u32,
 }
 
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct SpikeObs {
+    pub spawn_tick: u32,
+    /// Bottom edge, px; the spike reaches up to the top of the world.
+    pub bottom_px: i32,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct PlatformObs {
+    pub spawn_tick: u32,
+    /// Bob-center top edge, px.
+    pub base_y_px: i32,
+    pub phase0: u32,
+}
+
 #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
 pub struct SchedItem {
     pub spawn_tick: u32,
@@ -239,6 +422,15 @@ pub struct SchedItem {
     pub y_px: i32,
 }
 
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct SchedCoin {
+    pub spawn_tick: u32,
+    /// Offset behind the arc's first coin, px.
+    pub dx_px: i32,
+    /// Top edge, px.
+    pub y_px: i32,
+}
+
 /// The full obstacle/item timeline for one seed, split by kind so the
 /// circuit can use cheaper templates for ground obstacles than for bats.
 #[derive(Clone)]
@@ -249,14 +441,78 @@ pub struct Schedule {
     pub bat_count: usize,
     pub items: [SchedItem; MAX_SCHED_ITEMS],
     pub item_count: usize,
+    /// Always empty outside [`Schedule::with_hazards`].
+    pub spikes: [SpikeObs; MAX_SPIKES],
+    pub spike_count: usize,
+    pub platforms: [PlatformObs; MAX_PLATFORMS],
+    pub platform_count: usize,
+    /// Always empty outside [`Schedule::with_coins`].
+    pub coins: [SchedCoin; MAX_SCHED_COINS],
+    pub coin_count: usize,
+    /// First spawn tick each full list dropped, by [`Capacity`]
+    /// discriminant; see [`Schedule::dropped`].
+    drops: [Option<u32>; Capacity::ALL.len()],
+}
+
+/// Unranked schedule variants.
+#[derive(Clone, Copy, Default)]
+struct Rules {
+    hazards: bool,
+    coins: bool,
+    chunks: bool,
 }
 
 impl Schedule {
     /// Derives the schedule for `seed`. Mirrors `dash_core` spawn logic at
     /// 30 Hz. RNG call order is fixed; capacity overflow skips the spawn
-    /// (never reached in a 2-minute run in practice).
+    /// and is reported by [`Schedule::dropped`] (close to a third of seeds
+    /// run out of bat slots late in the run).
     pub fn generate(seed: u64) -> Self {
+        Self::build(seed, Rules::default())
+    }
+
+    /// Like [`Schedule::generate`], but ceiling spikes and moving platforms
+    /// join the obstacle rolls exactly as in `dash_core`'s hazards mode.
+    /// Unranked: the circuit only accepts [`Schedule::generate`].
+    pub fn with_hazards(seed: u64) -> Self {
+        Self::build(
+            seed,
+            Rules {
+                hazards: true,
+                ..Rules::default()
+            },
+        )
+    }
+
+    /// Like [`Schedule::generate`], plus coin arcs drawn after each tick's
+    /// item roll, as in `dash_core`'s coin mode. Unranked.
+    pub fn with_coins(seed: u64) -> Self {
+        Self::build(
+            seed,
+            Rules {
+                coins: true,
+                ..Rules::default()
+            },
+        )
+    }
+
+    /// Like [`Schedule::generate`], but obstacles come from the
+    /// `dario_levels` chunk sequence of `seed`, placed on the chunk clock
+    /// exactly as in `dash_core`'s chunked levels. Items keep their rolls.
+    /// Unranked.
+    pub fn with_chunks(seed: u64) -> Self {
+        Self::build(
+            seed,
+            Rules {
+                chunks: true,
+                ..Rules::default()
+            },
+        )
+    }
+
+    fn build(seed: u64, rules: Rules) -> Self {
         let mut rng = Rng::new(seed);
+        let mut chunker = Chunker::new(seed);
         let mut sched = Schedule {
             ground: [GroundObs::default(); MAX_GROUND],
             ground_count: 0,
@@ -264,50 +520,95 @@ impl Schedule {
             bat_count: 0,
             items: [SchedItem::default(); MAX_SCHED_ITEMS],
             item_count: 0,
+            spikes: [SpikeObs::default(); MAX_SPIKES],
+            spike_count: 0,
+            platforms: [PlatformObs::default(); MAX_PLATFORMS],
+            platform_count: 0,
+            coins: [SchedCoin::default(); MAX_SCHED_COINS],
+            coin_count: 0,
+            drops: [None; Capacity::ALL.len()],
         };
         let mut spawn_in: u32 = 42; // 1.4 s
         let mut item_in: u32 = 48; // 1.6 s
+        let mut coin_in: u32 = 75; // 2.5 s
         for t in 1..=MAX_TICKS {
-            spawn_in = spawn_in.saturating_sub(1);
-            if spawn_in == 0 {
-                let roll = rng.below(100);
-                if roll < 60 {
-                    let w = 34 + rng.below(19) as i32;
-                    let h = 42 + rng.below(35) as i32;
-                    if sched.ground_count < MAX_GROUND {
-                        sched.ground[sched.ground_count] = GroundObs {
-                            spawn_tick: t,
-                            kind: KIND_BARREL,
-                            w,
-                            h,
-                        };
-                        sched.ground_count += 1;
-                    }
-                } else if roll < 85 {
-                    let h = 84 + rng.below(31) as i32;
-                    if sched.ground_count < MAX_GROUND {
-                        sched.ground[sched.ground_count] = GroundObs {
-                            spawn_tick: t,
-                            kind: KIND_PIPE,
-                            w: 46,
-                            h,
-                        };
-                        sched.ground_count += 1;
-                    }
-                } else {
-                    let base_y_px = GROUND_Y - 90 - rng.below(71) as i32;
-                    let phase0 = rng.below(BAT_PERIOD);
-                    if sched.bat_count < MAX_BATS {
-                        sched.bats[sched.bat_count] = BatObs {
-                            spawn_tick: t,
-                            base_y_px,
-                            phase0,
-                        };
-                        sched.bat_count += 1;
+            if rules.chunks {
+                let clock = t * dario_levels::CLOCK_PX_PER_SEC / TICK_HZ;
+                while let Some(piece) = chunker.poll(clock) {
+                    sched.push_piece(t, piece);
+                }
+            } else {
+                spawn_in = spawn_in.saturating_sub(1);
+                if spawn_in == 0 {
+                    let roll = rng.below(if rules.hazards { 120 } else { 100 });
+                    if roll < 60 {
+                        let w = 34 + rng.below(19) as i32;
+                        let h = 42 + rng.below(35) as i32;
+                        if sched.ground_count < MAX_GROUND {
+                            sched.ground[sched.ground_count] = GroundObs {
+                                spawn_tick: t,
+                                kind: KIND_BARREL,
+                                w,
+                                h,
+                            };
+                            sched.ground_count += 1;
+                        } else {
+                            sched.drop_spawn(Capacity::Ground, t);
+                        }
+                    } else if roll < 85 {
+                        let h = 84 + rng.below(31) as i32;
+                        if sched.ground_count < MAX_GROUND {
+                            sched.ground[sched.ground_count] = GroundObs {
+                                spawn_tick: t,
+                                kind: KIND_PIPE,
+                                w: 46,
+                                h,
+                            };
+                            sched.ground_count += 1;
+                        } else {
+                            sched.drop_spawn(Capacity::Ground, t);
+                        }
+                    } else if roll < 100 {
+                        let base_y_px = GROUND_Y - 90 - rng.below(71) as i32;
+                        let phase0 = rng.below(BAT_PERIOD);
+                        if sched.bat_count < MAX_BATS {
+                            sched.bats[sched.bat_count] = BatObs {
+                                spawn_tick: t,
+                                base_y_px,
+                                phase0,
+                            };
+                            sched.bat_count += 1;
+                        } else {
+                            sched.drop_spawn(Capacity::Bats, t);
+                        }
+                    } else if roll < 110 {
+                        let bottom_px = 186 + rng.below(36) as i32;
+                        if sched.spike_count < MAX_SPIKES {
+                            sched.spikes[sched.spike_count] = SpikeObs {
+                                spawn_tick: t,
+                                bottom_px,
+                            };
+                            sched.spike_count += 1;
+                        } else {
+                            sched.drop_spawn(Capacity::Spikes, t);
+                        }
+                    } else {
+                        let base_y_px = GROUND_Y - 110 - rng.below(41) as i32;
+                        let phase0 = rng.below(PLATFORM_PERIOD);
+                        if sched.platform_count < MAX_PLATFORMS {
+                            sched.platforms[sched.platform_count] = PlatformObs {
+                                spawn_tick: t,
+                                base_y_px,
+                                phase0,
+                            };
+                            sched.platform_count += 1;
+                        } else {
+                            sched.drop_spawn(Capacity::Platforms, t);
+                        }
                     }
+                    let gap = 22 + rng.below(28);
+                    spawn_in = ((i64::from(gap) * SPEED_BASE100) / s100(t)) as u32 + 8;
                 }
-                let gap = 22 + rng.below(28);
-                spawn_in = ((i64::from(gap) * SPEED_BASE100) / s100(t)) as u32 + 8;
             }
             item_in = item_in.saturating_sub(1);
             if item_in == 0 {
@@ -325,12 +626,80 @@ impl Schedule {
                         y_px,
                     };
                     sched.item_count += 1;
+                } else {
+                    sched.drop_spawn(Capacity::Items, t);
                 }
                 item_in = 66 + rng.below(97);
             }
+            if rules.coins {
+                coin_in -= 1;
+                if coin_in == 0 {
+                    for (i, h) in COIN_ARC.into_iter().enumerate() {
+                        if sched.coin_count < MAX_SCHED_COINS {
+                            sched.coins[sched.coin_count] = SchedCoin {
+                                spawn_tick: t,
+                                dx_px: i as i32 * COIN_SPACING,
+                                y_px: GROUND_Y - h - COIN_SIZE,
+                            };
+                            sched.coin_count += 1;
+                        } else {
+                            sched.drop_spawn(Capacity::Coins, t);
+                        }
+                    }
+                    coin_in = 60 + rng.below(60);
+                }
+            }
         }
         sched
     }
+
+    /// Appends a chunk piece spawning at tick `t`; capacity overflow drops
+    /// it like the rolled spawns.
+    fn push_piece(&mut self, t: u32, piece: Piece) {
+        match piece {
+            Piece::Barrel { w, h } if self.ground_count < MAX_GROUND => {
+                self.ground[self.ground_count] = GroundObs {
+                    spawn_tick: t,
+                    kind: KIND_BARREL,
+                    w: i32::from(w),
+                    h: i32::from(h),
+                };
+                self.ground_count += 1;
+            }
+            Piece::Pipe { h } if self.ground_count < MAX_GROUND => {
+                self.ground[self.ground_count] = GroundObs {
+                    spawn_tick: t,
+                    kind: KIND_PIPE,
+                    w: 46,
+                    h: i32::from(h),
+                };
+                self.ground_count += 1;
+            }
+            // Phases are in 60 Hz ticks; the 30 Hz hover runs at half.
+            Piece::Bat { lift, phase } if self.bat_count < MAX_BATS => {
+                self.bats[self.bat_count] = BatObs {
+                    spawn_tick: t,
+                    base_y_px: GROUND_Y - i32::from(lift),
+                    phase0: u32::from(phase) / 2,
+                };
+                self.bat_count += 1;
+            }
+            Piece::Bat { .. } => self.drop_spawn(Capacity::Bats, t),
+            _ => self.drop_spawn(Capacity::Ground, t),
+        }
+    }
+
+    fn drop_spawn(&mut self, capacity: Capacity, t: u32) {
+        self.drops[capacity as usize].get_or_insert(t);
+    }
+
+    /// Each list that dropped spawns, with the first dropped spawn tick.
+    pub fn dropped(&self) -> impl Iterator<Item = (Capacity, u32)> + '_ {
+        Capacity::ALL
+            .into_iter()
+            .zip(self.drops)
+            .filter_map(|(c, tick)| Some((c, tick?)))
+    }
 }
 
 /// Left edge (fp100) at tick `t` of a world entity spawned at `spawn_tick`
@@ -367,11 +736,16 @@ pub struct PickupEv {
     pub item_idx: u32,
 }
 
+/// [`KillEv::target_class`] of a ground obstacle.
+pub const TARGET_GROUND: u32 = 0;
+/// [`KillEv::target_class`] of a bat.
+pub const TARGET_BAT: u32 = 1;
+
 #[derive(Clone, Copy, Default, Debug)]
 pub struct KillEv {
     pub fire_tick: u32,
     pub hit_tick: u32,
-    /// 0 = ground obstacle, 1 = bat.
+    /// [`TARGET_GROUND`] or [`TARGET_BAT`].
     pub target_class: u32,
     pub target_idx: u32,
 }
@@ -410,6 +784,15 @@ pub struct ZkSim {
     ticks: u32,
     over: bool,
     form: DarioState,
+    /// Tick the current form was entered.
+    form_since: u32,
+    /// Power-up lifetime in ticks; `None` (ranked) keeps forms until damage.
+    form_expiry: Option<u32>,
+    /// One-slot reserve item; only used when `reserve_enabled` (unranked).
+    reserve: Option<PowerUp>,
+    reserve_enabled: bool,
+    coins_enabled: bool,
+    chunks_enabled: bool,
     score: u64,
     pickups_n: u32,
     kills_n: u32,
@@ -422,6 +805,7 @@ pub struct ZkSim {
     ground_status: [ObsStatus; MAX_GROUND],
     bat_status: [ObsStatus; MAX_BATS],
     item_taken: [bool; MAX_SCHED_ITEMS],
+    coin_taken: [bool; MAX_SCHED_COINS],
     // In-flight fireballs: fire tick + top edge (fp), plus kill flag.
     fires: [(u32, i32, bool); MAX_FIRES_TRACKED],
     fire_count: usize,
@@ -434,6 +818,21 @@ pub struct ZkSim {
     pub kill_count: usize,
     pub damages: [DamageEv; MAX_DAMAGES + MAX_GROUND],
     pub damage_count: usize,
+    /// Ticks at which a powered form expired back to `Regular`.
+    pub expiries: [u32; MAX_EXPIRIES],
+    pub expiry_count: usize,
+    /// Ticks at which the reserve item was deployed.
+    pub deploys: [u32; MAX_DEPLOYS],
+    pub deploy_count: usize,
+    /// Coin pickups; `item_idx` indexes the schedule's coins.
+    pub coin_pickups: [PickupEv; MAX_SCHED_COINS],
+    pub coin_pickup_count: usize,
+    /// Rolling hash of the state after every tick once `hashing` is on,
+    /// for desync detection.
+    state_hash: u64,
+    hashing: bool,
+    /// [`Capacity`] bits that ran out so far; output only.
+    exhausted: u16,
 }
 
 impl ZkSim {
@@ -444,6 +843,12 @@ impl ZkSim {
             ticks: 0,
             over: false,
             form: DarioState::Regular,
+            form_since: 0,
+            form_expiry: None,
+            reserve: None,
+            reserve_enabled: false,
+            coins_enabled: false,
+            chunks_enabled: false,
             score: 0,
             pickups_n: 0,
             kills_n: 0,
@@ -454,6 +859,7 @@ impl ZkSim {
             ground_status: [ObsStatus::Cleared; MAX_GROUND],
             bat_status: [ObsStatus::Cleared; MAX_BATS],
             item_taken: [false; MAX_SCHED_ITEMS],
+            coin_taken: [false; MAX_SCHED_COINS],
             fires: [(0, 0, false); MAX_FIRES_TRACKED],
             fire_count: 0,
             jumps: [JumpEv::default(); MAX_JUMPS],
@@ -464,6 +870,55 @@ impl ZkSim {
             kill_count: 0,
             damages: [DamageEv::default(); MAX_DAMAGES + MAX_GROUND],
             damage_count: 0,
+            expiries: [0; MAX_EXPIRIES],
+            expiry_count: 0,
+            deploys: [0; MAX_DEPLOYS],
+            deploy_count: 0,
+            coin_pickups: [PickupEv::default(); MAX_SCHED_COINS],
+            coin_pickup_count: 0,
+            state_hash: 0,
+            hashing: false,
+            exhausted: 0,
+        }
+    }
+
+    /// Like [`ZkSim::new`], but with a one-slot reserve deployed by
+    /// [`INPUT_USE`]. The circuit has no reserve, so such runs are unranked.
+    pub fn with_reserve(seed: u64) -> Self {
+        Self {
+            reserve_enabled: true,
+            ..Self::new(seed)
+        }
+    }
+
+    /// Like [`ZkSim::new`], but powered forms revert to `Regular` after
+    /// `expire_ticks` ticks. The circuit has no notion of expiry, so such
+    /// runs are unranked and cannot be proven.
+    pub fn with_form_expiry(seed: u64, expire_ticks: u32) -> Self {
+        Self {
+            form_expiry: Some(expire_ticks),
+            ..Self::new(seed)
+        }
+    }
+
+    /// Like [`ZkSim::new`], but on [`Schedule::with_coins`]: coin arcs
+    /// spawn and each coin adds [`COIN_SCORE`]. The circuit has no coins
+    /// yet, so such runs are unranked.
+    pub fn with_coins(seed: u64) -> Self {
+        Self {
+            sched: Schedule::with_coins(seed),
+            coins_enabled: true,
+            ..Self::new(seed)
+        }
+    }
+
+    /// Like [`ZkSim::new`], but on [`Schedule::with_chunks`]. The contract
+    /// only recomputes the classic schedule, so such runs are unranked.
+    pub fn with_chunks(seed: u64) -> Self {
+        Self {
+            sched: Schedule::with_chunks(seed),
+            chunks_enabled: true,
+            ..Self::new(seed)
         }
     }
 
@@ -505,15 +960,155 @@ impl ZkSim {
     }
 
     fn update_score(&mut self, t: u32) {
-        self.score = ranked_score(t, self.pickups_n, self.kills_n);
+        self.score = score_with_coins(
+            t,
+            self.pickups_n,
+            self.kills_n,
+            self.coin_pickup_count as u32,
+        );
     }
 
     fn can_record_form_event(&self) -> bool {
         self.pickup_count + self.damage_count < MAX_FORM_EVENTS
     }
 
+    /// Whether an event logged at `count` of `max` fits, noting the
+    /// capacity that ran out when it does not.
+    fn log_room(&mut self, count: usize, max: usize, capacity: Capacity) -> bool {
+        if count >= max {
+            self.exhausted |= capacity.bit();
+            false
+        } else {
+            true
+        }
+    }
+
+    /// As [`ZkSim::log_room`], for a pickup or damage, which also share
+    /// [`MAX_FORM_EVENTS`].
+    fn room_for(&mut self, count: usize, max: usize, capacity: Capacity) -> bool {
+        if !self.log_room(count, max, capacity) {
+            false
+        } else if !self.can_record_form_event() {
+            self.exhausted |= Capacity::FormEvents.bit();
+            false
+        } else {
+            true
+        }
+    }
+
+    fn loadout(&self) -> Loadout {
+        Loadout {
+            form: self.form,
+            reserve: self.reserve,
+        }
+    }
+
+    fn set_loadout(&mut self, loadout: Loadout, t: u32) {
+        self.reserve = loadout.reserve;
+        self.set_form(loadout.form, t);
+    }
+
+    fn set_form(&mut self, form: DarioState, t: u32) {
+        if form != self.form {
+            self.form_since = t;
+        }
+        self.form = form;
+    }
+
     /// Advance one tick. Input bits: 1 = jump, 2 = fire.
     pub fn tick(&mut self, input: u8) {
+        let ticks = self.ticks;
+        self.advance(input);
+        if self.hashing && self.ticks != ticks {
+            self.state_hash = self.rolled_hash();
+        }
+    }
+
+    /// Like [`ZkSim::tick`], but fails on the tick a capacity first runs
+    /// out. The tick happens either way, so the run stays provable; the
+    /// error names what it silently changed from then on. When several run
+    /// out on one tick, the first in [`Capacity::ALL`] order is returned
+    /// and [`ZkSim::exhausted`] lists them all.
+    pub fn try_tick(&mut self, input: u8) -> Result<(), CapacityError> {
+        let before = self.exhausted;
+        self.tick(input);
+        let new = self.exhausted & !before;
+        match Capacity::ALL.into_iter().find(|c| new & c.bit() != 0) {
+            None => Ok(()),
+            Some(c) if c.is_schedule() => Err(CapacityError::ScheduleFull(c)),
+            Some(c) => Err(CapacityError::LogFull(c)),
+        }
+    }
+
+    /// Folds the full post-tick state into the previous `state_hash`.
+    fn rolled_hash(&self) -> u64 {
+        fn status(s: ObsStatus) -> u64 {
+            match s {
+                ObsStatus::Cleared => 0,
+                ObsStatus::Killed(t) => 1 << 32 | u64::from(t),
+                ObsStatus::Damaged(t) => 2 << 32 | u64::from(t),
+                ObsStatus::InvulnTouch(t) => 3 << 32 | u64::from(t),
+            }
+        }
+        let opt = |v: Option<u32>| v.map_or(u64::MAX, u64::from);
+        let counts = [
+            self.jump_count,
+            self.pickup_count,
+            self.kill_count,
+            self.damage_count,
+            self.expiry_count,
+            self.deploy_count,
+            self.fire_count,
+        ]
+        .iter()
+        .fold(0u64, |acc, &n| acc << 9 | n as u64);
+        let mut h = self.state_hash;
+        for word in [
+            self.seed,
+            u64::from(self.ticks),
+            u64::from(self.over)
+                | u64::from(self.reserve_enabled) << 1
+                | u64::from(self.coins_enabled) << 2
+                | u64::from(self.chunks_enabled) << 3,
+            self.form as u64,
+            u64::from(self.form_since),
+            self.reserve.map_or(u64::MAX, |item| item as u64),
+            self.score,
+            u64::from(self.pickups_n) << 32 | u64::from(self.kills_n),
+            u64::from(self.prev_input),
+            self.cur_jump.map_or(u64::MAX, |j| j as u64),
+            opt(self.last_fire),
+            opt(self.last_damage),
+            counts,
+        ] {
+            h = desync::mix(h, word);
+        }
+        let statuses = self.ground_status.iter().chain(&self.bat_status);
+        for (i, &s) in statuses
+            .enumerate()
+            .filter(|(_, &s)| s != ObsStatus::Cleared)
+        {
+            h = desync::mix(h, (i as u64) << 40 ^ status(s));
+        }
+        for (i, _) in self
+            .item_taken
+            .iter()
+            .enumerate()
+            .filter(|(_, &taken)| taken)
+        {
+            h = desync::mix(h, i as u64);
+        }
+        for &coin in &self.coin_pickups[..self.coin_pickup_count] {
+            h = desync::mix(h, u64::from(coin.tick) << 32 | u64::from(coin.item_idx));
+        }
+        for &(tf, top, killed) in &self.fires[..self.fire_count] {
+            h = desync::mix(h, u64::from(tf) << 32 | u64::from(top as u32));
+            h = desync::mix(h, u64::from(killed));
+        }
+        h
+    }
+
+    fn advance(&mut self, input: u8) {
         if self.over || self.ticks >= MAX_TICKS {
             self.over = true;
             return;
@@ -524,9 +1119,42 @@ impl ZkSim {
 
         self.ticks += 1;
         let t = self.ticks;
+        for (capacity, tick) in self.sched.dropped() {
+            if tick <= t {
+                self.exhausted |= capacity.bit();
+            }
+        }
+
+        // Power-up expiry, before the jump so a jump on the expiry tick is
+        // already a Regular one. Suppressed past the timeline capacity.
+        if self.form_expiry.is_some() {
+            let next = step(self.form, None, t - self.form_since, self.form_expiry);
+            if next != self.form
+                && self.log_room(self.expiry_count, MAX_EXPIRIES, Capacity::Expiries)
+            {
+                self.expiries[self.expiry_count] = t;
+                self.expiry_count += 1;
+                self.set_form(next, t);
+            }
+        }
+
+        // Reserve deploy, also before the jump.
+        if pressed & INPUT_USE != 0 && self.reserve_enabled {
+            let next = self.loadout().deploy();
+            if next.form != self.form
+                && self.log_room(self.deploy_count, MAX_DEPLOYS, Capacity::Deploys)
+            {
+                self.deploys[self.deploy_count] = t;
+                self.deploy_count += 1;
+                self.set_loadout(next, t);
+            }
+        }
 
         // Jump (edge-triggered, only when grounded).
-        if pressed & INPUT_JUMP != 0 && self.grounded_at(t) && self.jump_count < MAX_JUMPS {
+        let jump = pressed & INPUT_JUMP != 0 && self.grounded_at(t);
+        if jump && self.jump_count == MAX_JUMPS {
+            self.exhausted |= Capacity::Jumps.bit();
+        } else if jump {
             let v0 = if self.form == DarioState::Super {
                 SUPER_JUMP_V
             } else {
@@ -598,8 +1226,7 @@ impl ZkSim {
             let y_overlap = ptop < iy + ITEM_SIZE * FP && pbot > iy;
             if x_overlap
                 && y_overlap
-                && self.pickup_count < MAX_PICKUPS
-                && self.can_record_form_event()
+                && self.room_for(self.pickup_count, MAX_PICKUPS, Capacity::Pickups)
             {
                 self.item_taken[i] = true;
                 self.pickups[self.pickup_count] = PickupEv {
@@ -608,12 +1235,45 @@ impl ZkSim {
                 };
                 self.pickup_count += 1;
                 self.pickups_n += 1;
-                let ev = match it.kind {
-                    k if k == ITEM_CHILI => Event::ChiliPepper,
-                    k if k == ITEM_CAPE => Event::TableClothCape,
-                    _ => Event::Espresso,
+                let item = match it.kind {
+                    k if k == ITEM_CHILI => PowerUp::ChiliPepper,
+                    k if k == ITEM_CAPE => PowerUp::TableClothCape,
+                    _ => PowerUp::Espresso,
                 };
-                self.form = transition(self.form, ev);
+                if self.reserve_enabled {
+                    self.set_loadout(self.loadout().pick_up(item), t);
+                } else {
+                    self.set_form(transition(self.form, item.event()), t);
+                }
+            }
+        }
+
+        // Coin pickups, after items; coins never change the form.
+        for i in 0..self.sched.coin_count {
+            if self.coin_taken[i] {
+                continue;
+            }
+            let c = self.sched.coins[i];
+            if t < c.spawn_tick {
+                break;
+            }
+            let x = world_x100(ITEM_X0_100 + i64::from(c.dx_px) * FP100, c.spawn_tick, t);
+            let right = x + i64::from(COIN_SIZE) * FP100;
+            if right < -40 * FP100 {
+                continue;
+            }
+            let cy = c.y_px * FP;
+            if x < PLAYER_RIGHT100
+                && right > PLAYER_LEFT100
+                && ptop < cy + COIN_SIZE * FP
+                && pbot > cy
+            {
+                self.coin_taken[i] = true;
+                self.coin_pickups[self.coin_pickup_count] = PickupEv {
+                    tick: t,
+                    item_idx: i as u32,
+                };
+                self.coin_pickup_count += 1;
             }
         }
 
@@ -640,7 +1300,7 @@ impl ZkSim {
                 }
                 let (l, r, top, bot) = ground_hitbox(&g, t);
                 if fx < r && fright > l && fy < bot && fbot > top {
-                    hit = Some((0, gi as u32));
+                    hit = Some((TARGET_GROUND, gi as u32));
                     break;
                 }
             }
@@ -655,7 +1315,7 @@ impl ZkSim {
                     }
                     let (l, r, top, bot) = bat_hitbox(&b, t);
                     if fx < r && fright > l && fy < bot && fbot > top {
-                        hit = Some((1, bi as u32));
+                        hit = Some((TARGET_BAT, bi as u32));
                         break;
                     }
                 }
@@ -666,7 +1326,7 @@ impl ZkSim {
                 // (mirrors the jump-cap handling: suppress, don't desync).
                 if self.kill_count < MAX_KILLS {
                     self.fires[fi].2 = true;
-                    if class == 0 {
+                    if class == TARGET_GROUND {
                         self.ground_status[idx as usize] = ObsStatus::Killed(t);
                     } else {
                         self.bat_status[idx as usize] = ObsStatus::Killed(t);
@@ -679,6 +1339,8 @@ impl ZkSim {
                     };
                     self.kill_count += 1;
                     self.kills_n += 1;
+                } else {
+                    self.exhausted |= Capacity::Kills.bit();
                 }
             }
         }
@@ -719,7 +1381,7 @@ impl ZkSim {
             // Past the shared form-event capacity the circuit cannot express a
             // touched obstacle, so the collision is ignored entirely to keep
             // the sim and witness consistent (same rationale as the kill cap).
-            if self.damage_count < self.damages.len() && self.can_record_form_event() {
+            if self.room_for(self.damage_count, self.damages.len(), Capacity::FormEvents) {
                 if self.protected(t) {
                     let status = ObsStatus::InvulnTouch(t);
                     if class == 0 {
@@ -736,8 +1398,7 @@ impl ZkSim {
                         self.bat_status[idx as usize] = status;
                     }
                     self.push_damage(t, class, idx, false);
-                    let was = self.form;
-                    self.form = transition(was, Event::TakeDamage);
+                    self.set_form(transition(self.form, Event::TakeDamage), t);
                     if self.form == DarioState::GameOver {
                         self.over = true;
                     } else {
@@ -788,14 +1449,84 @@ impl ZkSim {
         self.score
     }
 
+    /// Where [`ZkSim::score`] came from, including what the cap removed.
+    pub fn score_breakdown(&self) -> ScoreBreakdown {
+        score_breakdown(
+            self.ticks,
+            self.pickups_n,
+            self.kills_n,
+            self.coin_pickup_count as u32,
+        )
+    }
+
     pub fn ticks(&self) -> u32 {
         self.ticks
     }
 
+    /// Rolling hash over every post-tick state since
+    /// [`ZkSim::hash_states`]; 0 before. See [`desync`].
+    pub fn state_hash(&self) -> u64 {
+        self.state_hash
+    }
+
+    /// Turns on [`ZkSim::state_hash`] from the next tick. Off by default:
+    /// folding the whole state every tick is wasted work when replaying a
+    /// trace only for its outcome, as `replay` and the zkVM guest do.
+    pub fn hash_states(&mut self) {
+        self.hashing = true;
+    }
+
     pub fn form(&self) -> u32 {
         self.form as u32
     }
 
+    /// True when the circuit can prove the run: classic schedule, no form
+    /// expiry, reserve or coins.
+    pub fn ranked(&self) -> bool {
+        self.form_expiry.is_none()
+            && !self.reserve_enabled
+            && !self.coins_enabled
+            && !self.chunks_enabled
+    }
+
+    /// Identifies the rule variants in replay files. Physics are pinned by
+    /// the circuit, so only form expiry, the reserve, coins and chunks
+    /// count.
+    pub fn config_hash(&self) -> u64 {
+        [
+            self.form_expiry.map_or(u64::MAX, u64::from),
+            u64::from(self.reserve_enabled),
+            u64::from(self.coins_enabled),
+            u64::from(self.chunks_enabled),
+        ]
+        .into_iter()
+        .fold(0x6461_7368_7a6b_6366, desync::mix)
+    }
+
+    pub fn form_expiry(&self) -> Option<u32> {
+        self.form_expiry
+    }
+
+    pub fn reserve_enabled(&self) -> bool {
+        self.reserve_enabled
+    }
+
+    /// Reserve item kind (`ITEM_*`), if one is stored.
+    pub fn reserve(&self) -> Option<u32> {
+        self.reserve.map(|item| item as u32)
+    }
+
+    /// Ticks until the current power-up expires, or `None` when forms do
+    /// not expire or Dario is not powered.
+    pub fn form_ticks_left(&self) -> Option<u32> {
+        match self.form_expiry {
+            Some(limit) if is_powered(self.form) => {
+                Some(limit.saturating_sub(self.ticks - self.form_since))
+            }
+            _ => None,
+        }
+    }
+
     pub fn pickups_total(&self) -> u32 {
         self.pickups_n
     }
@@ -804,6 +1535,18 @@ impl ZkSim {
         self.kills_n
     }
 
+    pub fn coins_total(&self) -> u32 {
+        self.coin_pickup_count as u32
+    }
+
+    pub fn coins_enabled(&self) -> bool {
+        self.coins_enabled
+    }
+
+    pub fn chunks_enabled(&self) -> bool {
+        self.chunks_enabled
+    }
+
     pub fn distance_px(&self) -> u64 {
         (d100(self.ticks) / (i64::from(FP) * 100)) as u64
     }
@@ -837,103 +1580,144 @@ impl ZkSim {
         &self.item_taken
     }
 
-    /// Render snapshot, same layout as `dash_core`:
-    /// `[entity_type, kind, x_px, y_px, w_px, h_px]` records; entity_type
-    /// 0 = obstacle (y = ground anchor for barrel/pipe, top for bat),
-    /// 1 = item, 2 = fireball.
-    pub fn snapshot(&self, out: &mut [i32]) -> usize {
-        let t = self.ticks;
-        let mut n = 0;
-        let mut push = |out: &mut [i32], rec: [i32; 6]| {
-            if n + 6 <= out.len() {
-                out[n..n + 6].copy_from_slice(&rec);
-                n += 6;
-            }
-        };
-        for gi in 0..self.sched.ground_count {
-            if self.ground_status[gi] != ObsStatus::Cleared {
-                continue;
-            }
-            let g = self.sched.ground[gi];
-            if t < g.spawn_tick {
-                break;
-            }
-            let x = world_x100(OBS_X0_100, g.spawn_tick, t);
-            if x + (g.w as i64) * FP100 < -60 * FP100 {
-                continue;
-            }
-            push(out, [0, g.kind, (x / FP100) as i32, GROUND_Y, g.w, g.h]);
-        }
-        for bi in 0..self.sched.bat_count {
-            if self.bat_status[bi] != ObsStatus::Cleared {
-                continue;
-            }
-            let b = self.sched.bats[bi];
-            if t < b.spawn_tick {
-                break;
-            }
-            let x = world_x100(OBS_X0_100, b.spawn_tick, t);
-            if x + (BAT_W as i64) * FP100 < -60 * FP100 {
-                continue;
-            }
-            push(
-                out,
-                [
-                    0,
-                    KIND_BAT,
-                    (x / FP100) as i32,
-                    bat_y_fp(&b, t) / FP,
-                    BAT_W,
-                    BAT_H,
-                ],
-            );
-        }
-        for i in 0..self.sched.item_count {
-            if self.item_taken[i] {
-                continue;
-            }
-            let it = self.sched.items[i];
-            if t < it.spawn_tick {
-                break;
-            }
-            let x = world_x100(ITEM_X0_100, it.spawn_tick, t);
-            if x + (ITEM_SIZE as i64) * FP100 < -40 * FP100 {
-                continue;
-            }
-            push(
-                out,
-                [
-                    1,
-                    it.kind,
-                    (x / FP100) as i32,
-                    it.y_px,
-                    ITEM_SIZE,
-                    ITEM_SIZE,
-                ],
-            );
-        }
-        for fi in 0..self.fire_count {
-            let (tf, fy, dead) = self.fires[fi];
-            if dead || t + 1 - tf > FIREBALL_LIFE {
-                continue;
-            }
-            let x = fireball_x100(tf, t);
-            push(
-                out,
-                [
-                    2,
-                    0,
-                    (x / FP100) as i32,
-                    fy / FP,
-                    FIREBALL_SIZE,
-                    FIREBALL_SIZE,
-                ],
-            );
+    /// Room left in each event log, to warn before one runs out.
+    pub fn remaining_capacity(&self) -> RemainingCapacity {
+        let form_events = self.pickup_count + self.damage_count;
+        RemainingCapacity {
+            jumps: MAX_JUMPS - self.jump_count,
+            pickups: MAX_PICKUPS - self.pickup_count,
+            kills: MAX_KILLS - self.kill_count,
+            form_events: MAX_FORM_EVENTS.saturating_sub(form_events),
+            expiries: MAX_EXPIRIES - self.expiry_count,
+            deploys: MAX_DEPLOYS - self.deploy_count,
         }
-        n
     }
+
+    /// Capacities that ran out so far this run: event logs that suppressed
+    /// an event, and schedule lists whose first dropped spawn is due.
+    pub fn exhausted(&self) -> impl Iterator<Item = Capacity> + '_ {
+        Capacity::ALL
+            .into_iter()
+            .filter(move |c| self.exhausted & c.bit() != 0)
+    }
+
+    /// Visible ground obstacles, then bats, in schedule order.
+    pub fn obstacles(&self) -> impl Iterator<Item = view::Obstacle> + '_ {
+        let t = self.ticks;
+        let ground = (0..self.sched.ground_count)
+            .take_while(move |&gi| t >= self.sched.ground[gi].spawn_tick)
+            .filter(|&gi| self.ground_status[gi] == ObsStatus::Cleared)
+            .filter_map(move |gi| {
+                let g = self.sched.ground[gi];
+                let x = world_x100(OBS_X0_100, g.spawn_tick, t);
+                (x + (g.w as i64) * FP100 >= -60 * FP100).then_some(view::Obstacle {
+                    id: gi as u32,
+                    kind: g.kind,
+                    x: (x / FP100) as i32,
+                    y: GROUND_Y,
+                    w: g.w,
+                    h: g.h,
+                })
+            });
+        let bats = (0..self.sched.bat_count)
+            .take_while(move |&bi| t >= self.sched.bats[bi].spawn_tick)
+            .filter(|&bi| self.bat_status[bi] == ObsStatus::Cleared)
+            .filter_map(move |bi| {
+                let b = self.sched.bats[bi];
+                let x = world_x100(OBS_X0_100, b.spawn_tick, t);
+                (x + (BAT_W as i64) * FP100 >= -60 * FP100).then_some(view::Obstacle {
+                    id: (MAX_GROUND + bi) as u32,
+                    kind: KIND_BAT,
+                    x: (x / FP100) as i32,
+                    y: bat_y_fp(&b, t) / FP,
+                    w: BAT_W,
+                    h: BAT_H,
+                })
+            });
+        ground.chain(bats)
+    }
+
+    /// Visible, untaken items in schedule order.
+    pub fn items(&self) -> impl Iterator<Item = view::Item> + '_ {
+        let t = self.ticks;
+        (0..self.sched.item_count)
+            .take_while(move |&i| t >= self.sched.items[i].spawn_tick)
+            .filter(|&i| !self.item_taken[i])
+            .filter_map(move |i| {
+                let it = self.sched.items[i];
+                let x = world_x100(ITEM_X0_100, it.spawn_tick, t);
+                (x + (ITEM_SIZE as i64) * FP100 >= -40 * FP100).then_some(view::Item {
+                    id: (MAX_GROUND + MAX_BATS + i) as u32,
+                    kind: it.kind,
+                    x: (x / FP100) as i32,
+                    y: it.y_px,
+                    size: ITEM_SIZE,
+                })
+            })
+    }
+
+    /// In-flight fireballs.
+    pub fn fireballs(&self) -> impl Iterator<Item = view::Fireball> + '_ {
+        let t = self.ticks;
+        self.fires[..self.fire_count]
+            .iter()
+            .filter(move |&&(tf, _, dead)| !dead && t + 1 - tf <= FIREBALL_LIFE)
+            .map(move |&(tf, fy, _)| view::Fireball {
+                id: (MAX_GROUND + MAX_BATS + MAX_SCHED_ITEMS) as u32 + tf,
+                x: (fireball_x100(tf, t) / FP100) as i32,
+                y: fy / FP,
+                size: FIREBALL_SIZE,
+            })
+    }
+
+    /// Visible, untaken coins in schedule order.
+    pub fn coins(&self) -> impl Iterator<Item = view::Coin> + '_ {
+        let t = self.ticks;
+        (0..self.sched.coin_count)
+            .take_while(move |&i| t >= self.sched.coins[i].spawn_tick)
+            .filter(|&i| !self.coin_taken[i])
+            .filter_map(move |i| {
+                let c = self.sched.coins[i];
+                let x = world_x100(ITEM_X0_100 + i64::from(c.dx_px) * FP100, c.spawn_tick, t);
+                (x + i64::from(COIN_SIZE) * FP100 >= -40 * FP100).then_some(view::Coin {
+                    id: COIN_ID_BASE + i as u32,
+                    x: (x / FP100) as i32,
+                    y: c.y_px,
+                    size: COIN_SIZE,
+                })
+            })
+    }
+
+    /// Every visible entity: obstacles, then items, then fireballs, then
+    /// coins.
+    pub fn entities(&self) -> impl Iterator<Item = view::Entity> + '_ {
+        self.obstacles()
+            .map(view::Entity::Obstacle)
+            .chain(self.items().map(view::Entity::Item))
+            .chain(self.fireballs().map(view::Entity::Fireball))
+            .chain(self.coins().map(view::Entity::Coin))
+    }
+
+    /// Render snapshot, same layout as `dash_core`: a
+    /// [`view::Entity::record`] per entity, in [`ZkSim::entities`] order.
+    /// Returns the number of `i32` values written.
+    pub fn snapshot(&self, out: &mut [i32]) -> usize {
+        view::write_records(self.entities(), out)
+    }
+
+    /// Maximum number of entities visible at once: every scheduled
+    /// obstacle, item and coin plus every tracked fireball.
+    pub const MAX_ENTITIES: usize =
+        MAX_GROUND + MAX_BATS + MAX_SCHED_ITEMS + MAX_FIRES_TRACKED + MAX_SCHED_COINS;
+
+    /// Maximum `snapshot` output length in i32s.
+    pub const SNAPSHOT_CAP: usize = Self::MAX_ENTITIES * view::RECORD_LEN;
 }
 
+/// First coin id: past every fireball id (fireball ids end at the last
+/// fire tick).
+const COIN_ID_BASE: u32 = (MAX_GROUND + MAX_BATS + MAX_SCHED_ITEMS) as u32 + MAX_TICKS + 1;
+
 /// Ground obstacle hitbox at tick `t`: (left100, right100, top_fp, bot_fp).
 pub fn ground_hitbox(g: &GroundObs, t: u32) -> (i64, i64, i32, i32) {
     let x = world_x100(OBS_X0_100, g.spawn_tick, t);
@@ -985,6 +1769,14 @@ mod tests {
             ranked_score(MAX_TICKS, MAX_PICKUPS as u32, MAX_KILLS as u32),
             MAX_RANKED_SCORE
         );
+        let b = score_breakdown(MAX_TICKS, MAX_PICKUPS as u32, MAX_KILLS as u32, 0);
+        assert_eq!(b.pickups, MAX_PICKUPS as u64 * 50);
+        assert_eq!(
+            b.raw(),
+            raw_ranked_score(MAX_TICKS, MAX_PICKUPS as u32, MAX_KILLS as u32)
+        );
+        assert_eq!(b.capped_by, b.raw() - MAX_RANKED_SCORE);
+        assert_eq!(score_breakdown(1, 0, 0, 0).capped_by, 0);
     }
 
     #[test]
@@ -997,6 +1789,8 @@ mod tests {
 
         assert!(sim.over());
         assert_eq!(sim.score(), MAX_RANKED_SCORE);
+        assert_eq!(sim.score_breakdown().total(), MAX_RANKED_SCORE);
+        assert!(sim.score_breakdown().capped_by > 0);
     }
 
     #[test]
@@ -1010,6 +1804,49 @@ mod tests {
 
         sim.damage_count -= 1;
         assert!(sim.can_record_form_event());
+
+        // Expiries and deploys have logs of their own.
+        sim.expiry_count = MAX_EXPIRIES - 1;
+        sim.deploy_count = MAX_DEPLOYS - 1;
+        assert!(sim.can_record_form_event());
+        assert_eq!(sim.remaining_capacity().form_events, 1);
+    }
+
+    #[test]
+    fn powered_forms_expire_only_when_enabled() {
+        for (mut sim, expires) in [
+            (ZkSim::new(1), false),
+            (ZkSim::with_form_expiry(1, 60), true),
+        ] {
+            sim.set_form(DarioState::Cape, 0);
+            for _ in 0..60 {
+                sim.tick(0);
+            }
+            if expires {
+                assert_eq!(sim.form(), DarioState::Regular as u32);
+                assert_eq!(sim.expiry_count, 1);
+                assert_eq!(sim.expiries[0], 60);
+            } else {
+                assert_eq!(sim.form(), DarioState::Cape as u32);
+                assert_eq!(sim.expiry_count, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn reserve_deploys_are_logged_only_when_enabled() {
+        for (mut sim, enabled) in [(ZkSim::new(1), false), (ZkSim::with_reserve(1), true)] {
+            sim.reserve = Some(PowerUp::TableClothCape);
+            sim.tick(INPUT_USE);
+            if enabled {
+                assert_eq!(sim.form(), DarioState::Cape as u32);
+                assert_eq!(sim.reserve(), None);
+                assert_eq!(&sim.deploys[..sim.deploy_count], &[1]);
+            } else {
+                assert_eq!(sim.form(), DarioState::Regular as u32);
+                assert_eq!(sim.deploy_count, 0);
+            }
+        }
     }
 
     #[test]
@@ -1076,6 +1913,80 @@ mod tests {
         }
     }
 
+    #[test]
+    fn hazards_only_appear_in_hazard_schedules() {
+        let ranked = Schedule::generate(42);
+        assert_eq!((ranked.spike_count, ranked.platform_count), (0, 0));
+
+        let sched = Schedule::with_hazards(42);
+        assert!(sched.spike_count > 0 && sched.platform_count > 0);
+        let spikes = &sched.spikes[..sched.spike_count];
+        assert!(spikes.windows(2).all(|w| w[0].spawn_tick < w[1].spawn_tick));
+        assert!(spikes.iter().all(|s| (186..222).contains(&s.bottom_px)));
+        let platforms = &sched.platforms[..sched.platform_count];
+        assert!(platforms.iter().all(|p| p.phase0 < PLATFORM_PERIOD));
+    }
+
+    #[test]
+    fn chunked_schedules_follow_the_chunk_clock() {
+        let sched = Schedule::with_chunks(7);
+        let mut chunker = Chunker::new(7);
+        let (mut gi, mut bi) = (0, 0);
+        let mut bat_dropped = None;
+        for t in 1..=MAX_TICKS {
+            while let Some(piece) = chunker.poll(t * dario_levels::CLOCK_PX_PER_SEC / TICK_HZ) {
+                match piece {
+                    // Pieces past capacity are skipped, like rolled spawns.
+                    Piece::Bat { .. } if bi == MAX_BATS => {
+                        bat_dropped.get_or_insert(t);
+                    }
+                    Piece::Bat { lift, .. } => {
+                        assert_eq!(sched.bats[bi].spawn_tick, t);
+                        assert_eq!(sched.bats[bi].base_y_px, GROUND_Y - i32::from(lift));
+                        bi += 1;
+                    }
+                    _ if gi == MAX_GROUND => {}
+                    _ => {
+                        assert_eq!(sched.ground[gi].spawn_tick, t);
+                        gi += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!((sched.ground_count, sched.bat_count), (gi, bi));
+        assert!(gi > 40);
+        let first_drop = |c| sched.dropped().find(|&(d, _)| d == c).map(|(_, t)| t);
+        assert_eq!(first_drop(Capacity::Bats), bat_dropped);
+        assert!(!ZkSim::with_chunks(7).ranked());
+    }
+
+    #[test]
+    fn try_tick_reports_each_capacity_once() {
+        // Nothing to hit and room for one more jump.
+        let mut sim = ZkSim::new(5);
+        sim.sched.ground_count = 0;
+        sim.sched.bat_count = 0;
+        sim.jump_count = MAX_JUMPS - 1;
+        assert_eq!(sim.remaining_capacity().jumps, 1);
+        let mut errors = 0;
+        for t in 0..200 {
+            let input = (t % 2) as u8 * INPUT_JUMP;
+            if let Err(e) = sim.try_tick(input) {
+                assert_eq!(e, CapacityError::LogFull(Capacity::Jumps));
+                errors += 1;
+            }
+        }
+        assert_eq!(errors, 1);
+        assert_eq!(sim.remaining_capacity().jumps, 0);
+        assert!(sim.exhausted().eq([Capacity::Jumps]));
+
+        // Busy ranked schedules can run out of bat slots late in the run.
+        let sched = Schedule::generate(11);
+        let (capacity, tick) = sched.dropped().next().unwrap();
+        assert_eq!(capacity, Capacity::Bats);
+        assert!(tick > sched.bats[MAX_BATS - 1].spawn_tick);
+    }
+
     #[test]
     fn idle_run_dies_or_caps() {
         let (score, ticks, over) = replay(42, &[0u8; MAX_TICKS as usize]);
@@ -1133,5 +2044,32 @@ mod tests {
             }
         }
         assert!(sim.ticks() <= MAX_TICKS);
+        assert_eq!(dario_replay::SimKind::Zk.max_ticks(), MAX_TICKS);
+    }
+
+    #[test]
+    fn entity_ids_follow_the_schedule() {
+        let mut sim = ZkSim::new(42);
+        let mut seen_bat = false;
+        for i in 0..900 {
+            sim.tick(if i % 25 < 6 { INPUT_JUMP } else { 0 });
+            if sim.over() {
+                break;
+            }
+            for o in sim.obstacles() {
+                let id = o.id as usize;
+                if o.kind == KIND_BAT {
+                    seen_bat = true;
+                    let bat = &sim.schedule().bats[id - MAX_GROUND];
+                    assert_eq!(bat_y_fp(bat, sim.ticks()) / FP, o.y);
+                } else {
+                    assert_eq!(sim.schedule().ground[id].kind, o.kind);
+                }
+            }
+            let mut buf = [0i32; ZkSim::SNAPSHOT_CAP];
+            let n = sim.snapshot(&mut buf);
+            assert_eq!(n, sim.entities().count() * 6);
+        }
+        assert!(seen_bat);
     }
 }
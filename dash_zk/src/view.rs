@@ -0,0 +1,9 @@
+//! Typed render views of the sim's entities.
+//!
+//! Same types as `dash_core::view`, from `dario_view`, but never a boss.
+//! Positions and sizes are in pixels. Ids derive from the schedule (ground
+//! index, then bats, then items), the fire tick for fireballs and the
+//! schedule again for coins, so they are stable across frames, unique
+//! within a run and identical on every replay of the seed.
+
+pub use dario_view::*;
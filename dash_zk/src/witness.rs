@@ -38,7 +38,9 @@ pub struct ObsWitness {
 }
 
 /// Merged form-event timeline entry.
-/// kind: 0 espresso, 1 chili, 2 cape, 3 damage, 4 invuln-touch (no-op).
+/// kind: 0 espresso, 1 chili, 2 cape, 3 damage, 4 invuln-touch (no-op),
+/// 5 expired (powered form timed out to Regular), 6 reserve deployed
+/// (5 and 6 only occur in unranked runs).
 #[derive(Clone, Copy, Default, Debug)]
 pub struct FormEv {
     pub tick: u32,
@@ -48,7 +50,10 @@ pub struct FormEv {
     pub form_after: u32,
 }
 
-pub const MAX_FORM_EVENTS: usize = crate::MAX_FORM_EVENTS;
+/// Timeline capacity: pickups and damage, plus the expiries and deploys of
+/// unranked runs, which have logs of their own.
+pub const MAX_FORM_EVENTS: usize =
+    crate::MAX_FORM_EVENTS + crate::MAX_EXPIRIES + crate::MAX_DEPLOYS;
 
 #[derive(Clone)]
 pub struct RunWitness {
@@ -65,6 +70,10 @@ pub struct RunWitness {
     pub pickup_count: usize,
     pub kills: [KillEv; MAX_KILLS],
     pub kill_count: usize,
+    /// Coin pickups (coin runs only; the circuit has no coin inputs yet,
+    /// so these are empty in every provable run).
+    pub coins: [PickupEv; MAX_SCHED_COINS],
+    pub coin_count: usize,
 }
 
 /// First tick `t >= spawn` where the obstacle's hitbox x-overlaps the
@@ -167,48 +176,66 @@ impl RunWitness {
             ow.jump_idx = found;
         }
 
-        // Merged form-event timeline (pickups + damages, by tick; sim
-        // processes pickups before damage within a tick).
+        // Merged form-event timeline, by tick. Within a tick the sim
+        // processes expiry, reserve deploy, pickups, then damage.
         let mut form_events = [FormEv::default(); MAX_FORM_EVENTS];
         let mut n = 0usize;
-        let mut pi = 0usize;
-        let mut di = 0usize;
-        let mut form = dario_fsm::DarioState::Regular;
-        while pi < sim.pickup_count || di < sim.damage_count {
-            let pt = if pi < sim.pickup_count {
-                sim.pickups[pi].tick
-            } else {
-                u32::MAX
-            };
-            let dt = if di < sim.damage_count {
-                sim.damages[di].tick
-            } else {
-                u32::MAX
+        let (mut ei, mut ui, mut pi, mut di) = (0usize, 0usize, 0usize, 0usize);
+        let mut loadout = dario_fsm::Loadout::new();
+        loop {
+            let heads = [
+                sim.expiries[..sim.expiry_count].get(ei).copied(),
+                sim.deploys[..sim.deploy_count].get(ui).copied(),
+                sim.pickups[..sim.pickup_count].get(pi).map(|p| p.tick),
+                sim.damages[..sim.damage_count].get(di).map(|d| d.tick),
+            ];
+            let Some((stream, tick)) = heads
+                .iter()
+                .enumerate()
+                .filter_map(|(i, t)| t.map(|t| (i, t)))
+                .min_by_key(|&(i, t)| (t, i))
+            else {
+                break;
             };
-            let (tick, kind) = if pt <= dt {
-                let it = sched.items[sim.pickups[pi].item_idx as usize];
-                pi += 1;
-                (pt, it.kind as u32)
-            } else {
-                let d = sim.damages[di];
-                di += 1;
-                (dt, if d.invuln_touch { 4 } else { 3 })
-            };
-            let ev = match kind {
-                0 => Some(dario_fsm::Event::Espresso),
-                1 => Some(dario_fsm::Event::ChiliPepper),
-                2 => Some(dario_fsm::Event::TableClothCape),
-                3 => Some(dario_fsm::Event::TakeDamage),
-                _ => None,
+            let kind = match stream {
+                0 => {
+                    ei += 1;
+                    loadout.form = dario_fsm::DarioState::Regular;
+                    5
+                }
+                1 => {
+                    ui += 1;
+                    loadout = loadout.deploy();
+                    6
+                }
+                2 => {
+                    let it = sched.items[sim.pickups[pi].item_idx as usize];
+                    pi += 1;
+                    if let Ok(item) = dario_fsm::PowerUp::try_from(it.kind as u32) {
+                        loadout = if sim.reserve_enabled() {
+                            loadout.pick_up(item)
+                        } else {
+                            loadout.apply(item.event())
+                        };
+                    }
+                    it.kind as u32
+                }
+                _ => {
+                    let d = sim.damages[di];
+                    di += 1;
+                    if d.invuln_touch {
+                        4
+                    } else {
+                        loadout = loadout.apply(dario_fsm::Event::TakeDamage);
+                        3
+                    }
+                }
             };
-            if let Some(ev) = ev {
-                form = dario_fsm::transition(form, ev);
-            }
             if n < MAX_FORM_EVENTS {
                 form_events[n] = FormEv {
                     tick,
                     kind,
-                    form_after: form as u32,
+                    form_after: loadout.form as u32,
                 };
                 n += 1;
             }
@@ -228,6 +255,8 @@ impl RunWitness {
             pickup_count: sim.pickup_count,
             kills: sim.kills,
             kill_count: sim.kill_count,
+            coins: sim.coin_pickups,
+            coin_count: sim.coin_pickup_count,
         }
     }
 }
@@ -270,7 +299,10 @@ mod tests {
 
     /// Autopilot that plays reasonably: jump over ground obstacles.
     fn autopilot_run(seed: u64) -> ZkSim {
-        let mut sim = ZkSim::new(seed);
+        autopilot(ZkSim::new(seed))
+    }
+
+    fn autopilot(mut sim: ZkSim) -> ZkSim {
         let mut snap = [0i32; 40 * 6];
         let mut prev = 0u8;
         while !sim.over() && sim.ticks() < MAX_TICKS {
@@ -399,6 +431,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn expiries_appear_in_form_timeline() {
+        let sim = autopilot(ZkSim::with_form_expiry(42, 45));
+        assert!(sim.expiry_count > 0, "seed must expire a power-up");
+        let w = RunWitness::extract(&sim);
+        let expired: alloc::vec::Vec<_> = w.form_events[..w.form_event_count]
+            .iter()
+            .filter(|ev| ev.kind == 5)
+            .collect();
+        assert_eq!(expired.len(), sim.expiry_count);
+        for ev in expired {
+            assert_eq!(ev.form_after, dario_fsm::DarioState::Regular as u32);
+        }
+    }
+
+    #[test]
+    fn coin_pickups_reach_the_witness_and_the_score() {
+        let sim = autopilot(ZkSim::with_coins(42));
+        assert!(!sim.ranked());
+        let w = RunWitness::extract(&sim);
+        assert!(w.coin_count > 0, "autopilot should run through a coin arc");
+        assert_eq!(w.coin_count as u32, sim.coins_total());
+        let sched = sim.schedule();
+        for pair in w.coins[..w.coin_count].windows(2) {
+            assert!(pair[0].item_idx < pair[1].item_idx);
+        }
+        for c in &w.coins[..w.coin_count] {
+            assert!(c.tick >= sched.coins[c.item_idx as usize].spawn_tick);
+        }
+        assert_eq!(
+            w.score,
+            score_with_coins(
+                w.ticks,
+                sim.pickups_total(),
+                sim.kills_total(),
+                sim.coins_total()
+            )
+        );
+        assert_eq!(RunWitness::extract(&autopilot_run(42)).coin_count, 0);
+    }
+
     #[test]
     fn bat_windows_are_short() {
         let sim = autopilot_run(42);
@@ -418,7 +491,7 @@ mod tests {
 
         let witness = RunWitness::extract(&sim);
         for kill in witness.kills.iter().take(witness.kill_count) {
-            let obstacle = if kill.target_class == 0 {
+            let obstacle = if kill.target_class == TARGET_GROUND {
                 witness.ground[kill.target_idx as usize]
             } else {
                 witness.bats[kill.target_idx as usize]
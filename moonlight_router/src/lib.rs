@@ -43,6 +43,11 @@ mod moonlight_router {
             abi::call::<_, ()>(contract, "submit_zk_run", &(seed, score, ticks, proof))
                 .unwrap_or_else(|err| panic!("MoonlightRouter: {err:?}"));
         }
+
+        pub fn set_daily_clock(&mut self, args: (ContractId, u32)) {
+            abi::call::<_, ()>(args.0, "set_daily_clock", &args.1)
+                .unwrap_or_else(|err| panic!("MoonlightRouter: {err:?}"));
+        }
     }
 
     impl Default for MoonlightRouter {
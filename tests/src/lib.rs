@@ -615,4 +615,148 @@ mod tests {
 
         Ok(())
     }
+
+    // --- Daily challenge ---
+    // 2026-10-19 UTC.
+    const DAILY_DAY: u32 = 20_745;
+    /// Must match the contract's `BLOCKS_PER_DAY`.
+    const BLOCKS_PER_DAY: u64 = 8_640;
+
+    /// Deploys the contract owned by `owner`, with the Moonlight router.
+    fn setup_daily(owner: &PublicKey) -> Result<(Session, ContractId), Error> {
+        let vm = VM::ephemeral()?;
+        let mut session = VM::genesis_session(&vm, 1);
+
+        let dario_id = session.deploy(
+            contract_bytecode!("contract"),
+            ContractData::builder().owner(owner.to_bytes()),
+            LIMIT,
+        )?;
+
+        session.deploy(
+            contract_bytecode!("moonlight_router"),
+            ContractData::builder()
+                .owner(OWNER)
+                .contract_id(TRANSFER_CONTRACT),
+            LIMIT,
+        )?;
+
+        Ok((session, dario_id))
+    }
+
+    fn routed_set_daily_clock(
+        session: &mut Session,
+        sender: PublicKey,
+        dario_id: ContractId,
+        day: u32,
+    ) -> Result<(), Error> {
+        with_public_sender(session, sender)?;
+        session.call::<_, ()>(
+            TRANSFER_CONTRACT,
+            "set_daily_clock",
+            &(dario_id, day),
+            LIMIT,
+        )?;
+        Ok(())
+    }
+
+    /// Submits a run on daily challenge `day` with a junk proof, so it
+    /// fails at the latest on proof verification; returns the error.
+    fn submit_daily(session: &mut Session, dario_id: ContractId, day: u32) -> String {
+        let seed = dash_zk::daily::daily_seed(day, dash_zk::daily::DAILY_DOMAIN);
+        let result = routed_submit_zk_run(
+            session,
+            moonlight_account(1),
+            dario_id,
+            seed,
+            ZK_FIXTURE_SCORE,
+            ZK_FIXTURE_TICKS,
+            ZK_FIXTURE_PROOF.to_vec(),
+        );
+        format!("{:?}", result.expect_err("a junk daily proof must fail"))
+    }
+
+    #[test]
+    pub fn test_daily_seed_matches_dash_zk() -> Result<(), Error> {
+        let (mut session, dario_id) = setup()?;
+
+        for day in [0, 1, DAILY_DAY, u32::MAX] {
+            assert_eq!(
+                session
+                    .call::<_, u64>(dario_id, "daily_seed", &day, LIMIT)?
+                    .data,
+                dash_zk::daily::daily_seed(day, dash_zk::daily::DAILY_DOMAIN)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_daily_clock_is_owner_only() -> Result<(), Error> {
+        let owner = moonlight_account(0);
+        let (mut session, dario_id) = setup_daily(&owner)?;
+
+        let result =
+            routed_set_daily_clock(&mut session, moonlight_account(1), dario_id, DAILY_DAY);
+        assert!(format!("{:?}", result.expect_err("non-owner"))
+            .contains("only the owner can set the daily clock"));
+        assert!(submit_daily(&mut session, dario_id, DAILY_DAY).contains("not open yet"));
+
+        routed_set_daily_clock(&mut session, owner, dario_id, DAILY_DAY)?;
+        assert!(submit_daily(&mut session, dario_id, DAILY_DAY).contains("invalid gameplay proof"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_daily_runs_are_only_open_on_their_day() -> Result<(), Error> {
+        let owner = moonlight_account(0);
+        let (mut session, dario_id) = setup_daily(&owner)?;
+
+        // No daily is open before the clock is set.
+        assert!(submit_daily(&mut session, dario_id, DAILY_DAY).contains("not open yet"));
+
+        routed_set_daily_clock(&mut session, owner, dario_id, DAILY_DAY)?;
+        assert!(submit_daily(&mut session, dario_id, DAILY_DAY + 1).contains("not open yet"));
+        assert!(
+            submit_daily(&mut session, dario_id, DAILY_DAY - 1).contains("daily challenge closed")
+        );
+        assert!(submit_daily(&mut session, dario_id, DAILY_DAY).contains("invalid gameplay proof"));
+
+        // A day of blocks later, yesterday's daily is closed.
+        session.set_meta(Metadata::BLOCK_HEIGHT, BLOCKS_PER_DAY)?;
+        assert!(submit_daily(&mut session, dario_id, DAILY_DAY).contains("daily challenge closed"));
+        assert!(
+            submit_daily(&mut session, dario_id, DAILY_DAY + 1).contains("invalid gameplay proof")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_daily_leaderboard_ranks_and_truncates() -> Result<(), Error> {
+        let (mut session, dario_id) = setup()?;
+        assert!(session
+            .call::<_, Vec<(String, u64, u32)>>(dario_id, "daily_leaderboard", &DAILY_DAY, LIMIT)?
+            .data
+            .is_empty());
+
+        // The contract ranks each day's entries with `daily::rank`.
+        let mut entries: Vec<(u32, u64, u32)> = (0..12)
+            .map(|a| (a, 100 * (a as u64 % 4), 900 + a))
+            .collect();
+        dash_zk::daily::rank(&mut entries);
+        let accounts: Vec<u32> = entries.iter().map(|e| e.0).collect();
+        assert_eq!(accounts, [3, 7, 11, 2, 6, 10, 1, 5, 9, 0]);
+        assert_eq!(entries.len(), dash_zk::daily::LEADERBOARD_LEN);
+
+        // An account's entry is only replaced by a better run.
+        assert!(dash_zk::daily::beats(301, 2000, (300, 900)));
+        assert!(dash_zk::daily::beats(300, 899, (300, 900)));
+        assert!(!dash_zk::daily::beats(300, 900, (300, 900)));
+        assert!(!dash_zk::daily::beats(299, 1, (300, 900)));
+
+        Ok(())
+    }
 }
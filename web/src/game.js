@@ -14,10 +14,15 @@ const TICK = 1 / 30;
 const ENT_OBSTACLE = 0;
 const ENT_ITEM = 1;
 const ENT_FIREBALL = 2;
+const ENT_COIN = 3; // coin mode only
+const ENT_BOSS = 4; // boss mode only; kind = hit points left
+const ENT_BOSS_SHOT = 5;
 // obstacle kinds
 const KIND_BARREL = 0;
 const KIND_PIPE = 1;
 const KIND_BAT = 2;
+const KIND_SPIKE = 3; // hazards mode only
+const KIND_PLATFORM = 4; // hazards mode only
 // item kinds
 const ITEMS = [
   { emoji: "\u2615", tint: "#f6d365" }, // espresso
@@ -147,6 +152,7 @@ export function createGame({ canvas, sprites, createSim, onGameOver, onHud }) {
       pickups: game.lastPickups,
       kills: game.lastKills,
       distance: game.distance,
+      breakdown: Array.from(game.sim.score_breakdown(), Number),
       seed: game.seed,
       trace: game.sim.trace(),
       sim: game.sim,
@@ -238,6 +244,28 @@ export function createGame({ canvas, sprites, createSim, onGameOver, onHud }) {
       return;
     }
 
+    if (kind === KIND_PLATFORM) {
+      // top edge at oy, like bats
+      ctx.fillStyle = "#6b4a2f";
+      ctx.fillRect(ox, oy, ow, oh);
+      ctx.fillStyle = "#9adf77";
+      ctx.fillRect(ox, oy, ow, 4);
+      return;
+    }
+
+    if (kind === KIND_SPIKE) {
+      // hangs from the top of the world down to oy
+      ctx.fillStyle = "#5b5f6b";
+      ctx.fillRect(ox + ow / 2 - 3, 0, 6, oh - 24);
+      ctx.fillStyle = "#c9ced8";
+      ctx.beginPath();
+      ctx.moveTo(ox, oy - 24);
+      ctx.lineTo(ox + ow, oy - 24);
+      ctx.lineTo(ox + ow / 2, oy);
+      ctx.fill();
+      return;
+    }
+
     const x = ox;
     const y = oy - oh; // barrel/pipe are anchored to the ground line
     if (kind === KIND_PIPE) {
@@ -296,11 +324,40 @@ export function createGame({ canvas, sprites, createSim, onGameOver, onHud }) {
         ctx.restore();
       }
 
+      for (let i = 0; i < snap.length; i += 6) {
+        if (snap[i] !== ENT_COIN) continue;
+        const [x, y, w] = [snap[i + 2], snap[i + 3], snap[i + 4]];
+        const spin = Math.abs(Math.cos(game.time * 5 + x * 0.03));
+        ctx.fillStyle = "#f2b705";
+        ctx.beginPath();
+        ctx.ellipse(x + w / 2, y + w / 2, (w / 2) * Math.max(spin, 0.2), w / 2, 0, 0, Math.PI * 2);
+        ctx.fill();
+      }
+
       for (let i = 0; i < snap.length; i += 6) {
         if (snap[i] !== ENT_OBSTACLE) continue;
         drawObstacle(snap[i + 1], snap[i + 2], snap[i + 3], snap[i + 4], snap[i + 5]);
       }
 
+      for (let i = 0; i < snap.length; i += 6) {
+        if (snap[i] === ENT_BOSS) {
+          const [hp, x, y, w, h] = [snap[i + 1], snap[i + 2], snap[i + 3], snap[i + 4], snap[i + 5]];
+          ctx.fillStyle = "#4b2a6b";
+          ctx.fillRect(x, y, w, h);
+          ctx.fillStyle = "#ffdf5d";
+          ctx.fillRect(x + 18, y + 30, 12, 12);
+          ctx.fillRect(x + w - 30, y + 30, 12, 12);
+          ctx.fillStyle = "#e14434";
+          for (let k = 0; k < hp; k++) ctx.fillRect(x + k * 8, y - 12, 6, 6);
+        } else if (snap[i] === ENT_BOSS_SHOT) {
+          const [x, y, w] = [snap[i + 2], snap[i + 3], snap[i + 4]];
+          ctx.fillStyle = "#b04ad0";
+          ctx.beginPath();
+          ctx.arc(x + w / 2, y + w / 2, w / 2, 0, Math.PI * 2);
+          ctx.fill();
+        }
+      }
+
       for (let i = 0; i < snap.length; i += 6) {
         if (snap[i] !== ENT_FIREBALL) continue;
         const [x, y, w, h] = [snap[i + 2], snap[i + 3], snap[i + 4], snap[i + 5]];
@@ -386,8 +443,9 @@ export function createGame({ canvas, sprites, createSim, onGameOver, onHud }) {
     }
   }
 
-  function start() {
-    game.seed = randomSeed();
+  // `seed` is a BigInt, e.g. a daily challenge seed; random by default.
+  function start(seed = randomSeed()) {
+    game.seed = seed;
     game.sim = createSim(game.seed);
     game.running = true;
     game.over = false;
@@ -2,7 +2,7 @@ import { createDuskApp, DUSK_CHAIN_PRESETS } from "@dusk/connect";
 import { defineDuskConnectButton } from "@dusk/connect/ui";
 import "./styles.css";
 
-import initDashWasm, { ZkDashSim } from "./dash-wasm/dash_web.js";
+import initDashWasm, { ZkDashSim, daily_seed, zk_achievements } from "./dash-wasm/dash_web.js";
 import { STATE } from "./fsm.js";
 import { createGame } from "./game.js";
 
@@ -55,6 +55,8 @@ const dusk = HAS_CONTRACT
             best_score_for: "best_score_for(String)",
             proven_runs_for: "proven_runs_for(String)",
             leaderboard: "leaderboard()",
+            daily_seed: "daily_seed(u32)",
+            daily_leaderboard: "daily_leaderboard(u32)",
           },
         },
       },
@@ -78,6 +80,7 @@ const elHint = $("hint");
 const elStartOverlay = $("startOverlay");
 const elStartText = $("startText");
 const elPlayBtn = $("playBtn");
+const elDailyBtn = $("dailyBtn");
 const elConnectStartBtn = $("connectStartBtn");
 const elOverOverlay = $("gameOverOverlay");
 const elOverScore = $("overScore");
@@ -178,6 +181,7 @@ function render() {
     elConnectStartBtn.hidden = !HAS_CONTRACT || isConnected;
   }
   if (elPlayBtn) elPlayBtn.disabled = !game;
+  if (elDailyBtn) elDailyBtn.disabled = !game;
 
   if (over && model.lastRun) {
     const run = model.lastRun;
@@ -187,7 +191,8 @@ function render() {
       }`;
     }
     if (elOverStats) {
-      elOverStats.textContent = `Best ${run.best} · ${run.pickups} power-ups · ${run.kills} fried · ${Math.floor(run.distance / 50)}m · ${run.ticks} ticks`;
+      const unlocked = run.achievements.length ? ` · Unlocked: ${run.achievements.join(", ")}` : "";
+      elOverStats.textContent = `Best ${run.best} · ${run.pickups} power-ups · ${run.kills} fried · ${Math.floor(run.distance / 50)}m · ${run.ticks} ticks · ${explainScore(run.breakdown)}${unlocked}`;
     }
     if (elDownloadRunBtn) {
       elDownloadRunBtn.hidden = !connected();
@@ -473,13 +478,20 @@ async function proveAndSubmit() {
   }
 }
 
-function startRun() {
+// Today's daily challenge: UTC days since the Unix epoch, the same day
+// index the contract's `daily_leaderboard(day)` uses.
+function todaySeed() {
+  return daily_seed(Math.floor(Date.now() / 86_400_000));
+}
+
+// `seed` defaults to a random free-play seed.
+function startRun(seed) {
   if (!game) return;
   model.liveForm = STATE.Regular;
   model.lastRun = null;
   model.submitted = false;
   model.submitError = null;
-  game.start();
+  game.start(seed);
   render();
 }
 
@@ -500,7 +512,8 @@ async function requestConnect() {
   }
 }
 
-elPlayBtn?.addEventListener("click", startRun);
+elPlayBtn?.addEventListener("click", () => startRun());
+elDailyBtn?.addEventListener("click", () => startRun(todaySeed()));
 elAgainBtn?.addEventListener("click", () => {
   if (!model.submitting) startRun();
 });
@@ -526,6 +539,18 @@ function onWalletState() {
   }
 }
 
+// Spells out a `score_breakdown()`: [distance, pickups, kills, coins,
+// bosses, capped_by].
+function explainScore([distance, pickups, kills, coins, bosses, cappedBy]) {
+  const parts = [`${distance} distance`];
+  if (pickups) parts.push(`${pickups} power-ups`);
+  if (kills) parts.push(`${kills} fried`);
+  if (coins) parts.push(`${coins} coins`);
+  if (bosses) parts.push(`${bosses} bosses`);
+  const sum = parts.join(" + ");
+  return cappedBy ? `${sum} − ${cappedBy} over the cap` : sum;
+}
+
 async function init() {
   render();
 
@@ -541,7 +566,7 @@ async function init() {
       }
     },
     onGameOver: (run) => {
-      model.lastRun = run;
+      model.lastRun = { ...run, achievements: zk_achievements(run.seed, run.trace) };
       model.submitted = false;
       model.submitError = null;
       render();
@@ -4,8 +4,11 @@
 //! Input (via `env::read_frame`-style raw bytes, written by the prover):
 //!   [0..96)    account   — compressed Moonlight BLS public key bytes
 //!   [96..104)  seed      — u64 little-endian
-//!   [104..108) trace_len — u32 little-endian
-//!   [108..)    trace     — one input byte per tick
+//!   [104..108) trace_len  — u32 little-endian, unpacked ticks
+//!   [108..112) packed_len — u32 little-endian
+//!   [112..)    trace      — run-length packed input bytes
+//!                           (`dario_replay::trace`), so the guest reads a
+//!                           few hundred bytes instead of one per tick
 //!
 //! Journal (committed, fixed layout):
 //!   account (96) || seed (8, LE) || score (8, LE) || ticks (4, LE)
@@ -15,7 +18,7 @@ use risc0_zkvm::guest::env;
 const ACCOUNT_LEN: usize = 96;
 
 fn main() {
-    let mut header = [0u8; ACCOUNT_LEN + 8 + 4];
+    let mut header = [0u8; ACCOUNT_LEN + 8 + 4 + 4];
     env::read_slice(&mut header);
 
     let mut account = [0u8; ACCOUNT_LEN];
@@ -23,14 +26,20 @@ fn main() {
     let seed = u64::from_le_bytes(header[ACCOUNT_LEN..ACCOUNT_LEN + 8].try_into().unwrap());
     let trace_len =
         u32::from_le_bytes(header[ACCOUNT_LEN + 8..ACCOUNT_LEN + 12].try_into().unwrap());
+    let packed_len =
+        u32::from_le_bytes(header[ACCOUNT_LEN + 12..ACCOUNT_LEN + 16].try_into().unwrap());
 
     assert!(
         trace_len <= dash_core::MAX_TICKS,
         "trace exceeds maximum run length"
     );
 
-    let mut trace = vec![0u8; trace_len as usize];
-    env::read_slice(&mut trace);
+    let mut packed = vec![0u8; packed_len as usize];
+    env::read_slice(&mut packed);
+    let mut trace = Vec::with_capacity(trace_len as usize);
+    dario_replay::trace::decode(&packed, &mut trace, trace_len as usize)
+        .expect("malformed packed trace");
+    assert_eq!(trace.len(), trace_len as usize, "trace length mismatch");
 
     let result = dash_core::replay(seed, &trace);
     assert!(result.over, "run did not end in game over");
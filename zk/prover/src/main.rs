@@ -4,8 +4,9 @@
 //!   export-constants <out_dir>   Write contract verification constants
 //!                                (image id, control root, bn254 control id,
 //!                                pvk blob, gamma_abc points).
-//!   prove <run.json> <out.json>  Prove a recorded run and emit a proof
-//!                                bundle ready for on-chain submission.
+//!   prove <run> <out.json>       Prove a recorded run (`.dario` replay or
+//!                                legacy run JSON) and emit a proof bundle
+//!                                ready for on-chain submission.
 //!
 //! All arkworks material is produced with ark 0.4 so byte formats match
 //! Dusk's `verify_groth16_bn254` host function exactly.
@@ -69,8 +70,8 @@ fn main() -> Result<()> {
             export_constants(out)
         }
         Some("prove") => {
-            let run = args.get(2).context("usage: prove <run.json> <out.json>")?;
-            let out = args.get(3).context("usage: prove <run.json> <out.json>")?;
+            let run = args.get(2).context("usage: prove <run.dario|run.json> <out.json>")?;
+            let out = args.get(3).context("usage: prove <run.dario|run.json> <out.json>")?;
             prove(run, out)
         }
         Some("export-snarkjs-vkey") => {
@@ -88,7 +89,7 @@ fn main() -> Result<()> {
             snark::verify_ark_proof(vkey, &proof_hex, publics)
         }
         _ => {
-            eprintln!("usage: dash-prover <export-constants [out_dir] | prove <run.json> <out.json> | export-snarkjs-vkey ... | verify-ark-proof ...>");
+            eprintln!("usage: dash-prover <export-constants [out_dir] | prove <run.dario|run.json> <out.json> | export-snarkjs-vkey ... | verify-ark-proof ...>");
             std::process::exit(2);
         }
     }
@@ -137,10 +138,26 @@ fn export_constants(out_dir: &str) -> Result<()> {
     Ok(())
 }
 
-fn prove(run_path: &str, out_path: &str) -> Result<()> {
-    let run: RunFile =
-        serde_json::from_str(&fs::read_to_string(run_path).context("reading run file")?)?;
+/// Reads a run from either a `.dario` replay of a `dash_core` run or a
+/// legacy `RunFile` JSON: (account, seed, trace).
+fn read_run(run_path: &str) -> Result<([u8; ACCOUNT_LEN], u64, Vec<u8>)> {
+    let bytes = fs::read(run_path).context("reading run file")?;
+    if dario_replay::is_replay(&bytes) {
+        let replay =
+            dario_replay::Replay::decode(&bytes).map_err(|e| anyhow!("invalid replay: {e:?}"))?;
+        if replay.kind != dario_replay::SimKind::Core {
+            bail!("replay is a dash_zk run; prove it in the browser instead");
+        }
+        if replay.config_hash != dash_core::SimConfig::NORMAL.hash() {
+            bail!("replay was recorded under an unranked config");
+        }
+        let account = replay
+            .account
+            .context("replay is not bound to an account")?;
+        return Ok((account, replay.seed, replay.trace));
+    }
 
+    let run: RunFile = serde_json::from_slice(&bytes)?;
     let account: [u8; ACCOUNT_LEN] = bs58::decode(&run.account)
         .into_vec()
         .context("invalid bs58 account")?
@@ -149,9 +166,14 @@ fn prove(run_path: &str, out_path: &str) -> Result<()> {
     let trace = base64::engine::general_purpose::STANDARD
         .decode(&run.trace_b64)
         .context("invalid base64 trace")?;
+    Ok((account, run.seed, trace))
+}
+
+fn prove(run_path: &str, out_path: &str) -> Result<()> {
+    let (account, seed, trace) = read_run(run_path)?;
 
     // Local pre-check so we fail fast on bad traces.
-    let expected = dash_core::replay(run.seed, &trace);
+    let expected = dash_core::replay(seed, &trace);
     if !expected.over {
         bail!("trace does not end in game over; refusing to prove");
     }
@@ -160,11 +182,19 @@ fn prove(run_path: &str, out_path: &str) -> Result<()> {
         expected.score, expected.ticks
     );
 
-    let mut input = Vec::with_capacity(ACCOUNT_LEN + 12 + trace.len());
+    // The guest reads the trace run-length packed; mask first so only
+    // input bits reach the codec (the sim ignores the rest anyway).
+    let masked: Vec<u8> = trace.iter().map(|b| b & dash_core::INPUT_MASK).collect();
+    let mut packed = Vec::new();
+    dario_replay::trace::encode(&masked, &mut packed)
+        .map_err(|e| anyhow!("packing trace: {e:?}"))?;
+
+    let mut input = Vec::with_capacity(ACCOUNT_LEN + 16 + packed.len());
     input.extend_from_slice(&account);
-    input.extend_from_slice(&run.seed.to_le_bytes());
-    input.extend_from_slice(&(trace.len() as u32).to_le_bytes());
-    input.extend_from_slice(&trace);
+    input.extend_from_slice(&seed.to_le_bytes());
+    input.extend_from_slice(&(masked.len() as u32).to_le_bytes());
+    input.extend_from_slice(&(packed.len() as u32).to_le_bytes());
+    input.extend_from_slice(&packed);
 
     let env = ExecutorEnv::builder()
         .write_slice(&input)
@@ -209,8 +239,8 @@ fn prove(run_path: &str, out_path: &str) -> Result<()> {
     };
 
     let bundle = ProofBundle {
-        account: run.account,
-        seed: run.seed,
+        account: bs58::encode(account).into_string(),
+        seed,
         score,
         ticks,
         proof_hex,